@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Where an action is used, two actions may only share a key if they are never used at the same time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Used on every screen
+    Global,
+    /// Used in the main menu and the settings
+    Menu,
+    /// Used while a match is running or paused
+    Match,
+}
+
+/// Everything the player can do with the keyboard, independent of the bound key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    #[serde(rename = "player_1_up")]
+    Player1Up,
+    #[serde(rename = "player_1_down")]
+    Player1Down,
+    #[serde(rename = "player_2_up")]
+    Player2Up,
+    #[serde(rename = "player_2_down")]
+    Player2Down,
+    Pause,
+    Back,
+    Quit,
+    SwitchDisplayMode,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Settings,
+    StartNormal,
+    StartHard,
+    #[serde(rename = "switch_player_1")]
+    SwitchPlayer1,
+    #[serde(rename = "switch_player_2")]
+    SwitchPlayer2,
+    WatchReplay,
+}
+
+impl Action {
+    /// All actions in the order of the controls screen
+    pub const ALL: [Action; 19] = [
+        Action::Player1Up,
+        Action::Player1Down,
+        Action::Player2Up,
+        Action::Player2Down,
+        Action::Pause,
+        Action::Back,
+        Action::Quit,
+        Action::SwitchDisplayMode,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuLeft,
+        Action::MenuRight,
+        Action::Confirm,
+        Action::Settings,
+        Action::StartNormal,
+        Action::StartHard,
+        Action::SwitchPlayer1,
+        Action::SwitchPlayer2,
+        Action::WatchReplay,
+    ];
+
+    pub fn scope(self) -> Scope {
+        match self {
+            Action::Back | Action::Quit | Action::SwitchDisplayMode => Scope::Global,
+            Action::MenuUp
+            | Action::MenuDown
+            | Action::MenuLeft
+            | Action::MenuRight
+            | Action::Confirm
+            | Action::Settings
+            | Action::StartNormal
+            | Action::StartHard
+            | Action::SwitchPlayer1
+            | Action::SwitchPlayer2
+            | Action::WatchReplay => Scope::Menu,
+            Action::Player1Up
+            | Action::Player1Down
+            | Action::Player2Up
+            | Action::Player2Down
+            | Action::Pause => Scope::Match,
+        }
+    }
+
+    // Check if the action can be triggered within the given scope
+    pub fn is_active_in(self, scope: Scope) -> bool {
+        self.scope() == scope || self.scope() == Scope::Global
+    }
+
+    // Check if both actions must not be bound to the same key,
+    // because they can be triggered at the same time
+    pub fn conflicts_with(self, other: Action) -> bool {
+        self != other && (self.is_active_in(other.scope()) || other.is_active_in(self.scope()))
+    }
+
+    // Name of the key, which is bound to the action by default
+    pub fn default_key(self) -> &'static str {
+        match self {
+            Action::Player1Up => "W",
+            Action::Player1Down => "S",
+            Action::Player2Up => "Up",
+            Action::Player2Down => "Down",
+            Action::Pause => "P",
+            Action::Back => "Backspace",
+            Action::Quit => "Escape",
+            Action::SwitchDisplayMode => "F11",
+            Action::MenuUp => "Up",
+            Action::MenuDown => "Down",
+            Action::MenuLeft => "Left",
+            Action::MenuRight => "Right",
+            Action::Confirm => "Enter",
+            Action::Settings => "O",
+            Action::StartNormal => "N",
+            Action::StartHard => "H",
+            Action::SwitchPlayer1 => "Num1",
+            Action::SwitchPlayer2 => "Num2",
+            Action::WatchReplay => "R",
+        }
+    }
+
+    // Names of the default keys of all actions, as they are stored in the configuration
+    pub fn default_bindings() -> BTreeMap<Action, String> {
+        Action::ALL
+            .iter()
+            .map(|action| (*action, action.default_key().to_owned()))
+            .collect()
+    }
+
+    // Key of the description within the string tables
+    pub fn text_key(self) -> &'static str {
+        match self {
+            Action::Player1Up => "action_player_1_up",
+            Action::Player1Down => "action_player_1_down",
+            Action::Player2Up => "action_player_2_up",
+            Action::Player2Down => "action_player_2_down",
+            Action::Pause => "menu_pause",
+            Action::Back => "action_back",
+            Action::Quit => "menu_quit",
+            Action::SwitchDisplayMode => "menu_display_mode",
+            Action::MenuUp => "action_menu_up",
+            Action::MenuDown => "action_menu_down",
+            Action::MenuLeft => "action_menu_left",
+            Action::MenuRight => "action_menu_right",
+            Action::Confirm => "action_confirm",
+            Action::Settings => "menu_settings",
+            Action::StartNormal => "action_start_normal",
+            Action::StartHard => "action_start_hard",
+            Action::SwitchPlayer1 => "action_switch_player_1",
+            Action::SwitchPlayer2 => "action_switch_player_2",
+            Action::WatchReplay => "menu_watch_replay",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_and_match_actions_may_share_a_key() {
+        assert!(!Action::MenuUp.conflicts_with(Action::Player2Up));
+        assert!(!Action::Player2Up.conflicts_with(Action::MenuUp));
+    }
+
+    #[test]
+    fn global_action_conflicts_with_every_scope() {
+        assert!(Action::Quit.conflicts_with(Action::Player1Up));
+        assert!(Action::Player1Up.conflicts_with(Action::Quit));
+        assert!(Action::Quit.conflicts_with(Action::MenuUp));
+        assert!(Action::Quit.conflicts_with(Action::Back));
+        assert!(!Action::Quit.conflicts_with(Action::Quit));
+    }
+}
@@ -0,0 +1,408 @@
+use crate::simulation::{PaddleIntent, Player, Simulation};
+use crate::trajectory::{self, Crossing};
+use crate::util::SIMULATION_TIMESTEP;
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use vek::Vec2;
+
+/// Skill levels of the computer opponent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    /// Learns the habits of the other player during the session
+    Adaptive,
+    Perfect,
+}
+
+/// Number of points on the paddle, which will be tried to aim a return
+const AIM_CANDIDATES: usize = 9;
+
+/// Part of the paddle height around its centre, which will be used to aim,
+/// the remaining part is a safety margin to hit the ball at all
+const AIM_RANGE: f32 = 0.8;
+
+/// Number of horizontal stripes of the playfield, in which the habits of a player are counted
+const ZONES: usize = 8;
+
+/// Number of returns the adaptive opponent needs to observe, before it trusts the habits
+const MIN_OBSERVATIONS: u32 = 5;
+
+/// Detour the adaptive opponent accepts to aim at a zone, where the other player always misses
+/// the ball, in units of the playfield
+const WEAKNESS_BONUS: f32 = 160.0;
+
+/// Additional ticks of reaction time per point the opponent leads with,
+/// if the difficulty follows the score
+const DYNAMIC_TICKS_PER_POINT: i32 = 3;
+
+/// Longest reaction time of an opponent, whose difficulty follows the score
+const DYNAMIC_MAX_TICKS: i32 = 45;
+
+/// Abilities of the computer opponent, which make up a difficulty
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skill {
+    /// Ticks until the opponent reacts to a ball, which changed its direction
+    pub reaction_ticks: u32,
+    /// 0.0 only follows the ball, 1.0 moves straight to the point where the ball will arrive,
+    /// including all reflections on the borders
+    pub prediction: f32,
+    /// Hit the ball with the part of the paddle, whose spin sends it furthest away
+    /// from the other paddle
+    pub aims: bool,
+    /// Learn where the other player returns the ball, how much spin the player uses
+    /// and where the player misses the ball
+    pub adapts: bool,
+    /// Part of the full paddle speed the opponent uses
+    pub speed: f32,
+    /// Maximum distance the opponent misjudges the arriving ball by, in units of the playfield
+    pub aim_error: f32,
+    /// Chance to misjudge a ball on purpose, so that it will be missed
+    pub mistake_chance: f64,
+}
+
+impl Difficulty {
+    pub fn skill(self) -> Skill {
+        match self {
+            Difficulty::Easy => Skill {
+                reaction_ticks: 18,
+                prediction: 0.0,
+                aims: false,
+                adapts: false,
+                speed: 0.55,
+                aim_error: 24.0,
+                mistake_chance: 0.25,
+            },
+            Difficulty::Normal => Skill {
+                reaction_ticks: 10,
+                prediction: 0.5,
+                aims: false,
+                adapts: false,
+                speed: 0.75,
+                aim_error: 16.0,
+                mistake_chance: 0.1,
+            },
+            Difficulty::Hard => Skill {
+                reaction_ticks: 5,
+                prediction: 0.85,
+                aims: false,
+                adapts: false,
+                speed: 0.9,
+                aim_error: 8.0,
+                mistake_chance: 0.03,
+            },
+            Difficulty::Adaptive => Skill {
+                reaction_ticks: 6,
+                prediction: 0.9,
+                aims: true,
+                adapts: true,
+                speed: 0.9,
+                aim_error: 8.0,
+                mistake_chance: 0.05,
+            },
+            Difficulty::Perfect => Skill {
+                reaction_ticks: 0,
+                prediction: 1.0,
+                aims: true,
+                adapts: false,
+                speed: 1.0,
+                aim_error: 0.0,
+                mistake_chance: 0.0,
+            },
+        }
+    }
+}
+
+/// Habits of a player, which an adaptive opponent observed during the session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Habits {
+    /// Number of returns of the player, which arrived in each zone of the opponent's side
+    returns: [u32; ZONES],
+    /// Number of balls the player reached in each zone of the player's own side
+    hits: [u32; ZONES],
+    /// Number of balls the player missed in each zone of the player's own side
+    misses: [u32; ZONES],
+    /// Sum of the changes of the vertical ball speed by the returns of the player
+    spin_total: f32,
+    spin_count: u32,
+}
+
+impl Habits {
+    // Zone of the given height within the playfield
+    fn zone(y: f32, field_height: f32) -> usize {
+        ((y / field_height * ZONES as f32).max(0.0) as usize).min(ZONES - 1)
+    }
+
+    // Average height, where the returns of the player arrive
+    // - returns nothing, until enough returns have been observed
+    pub fn expected_return(&self, field_height: f32) -> Option<f32> {
+        let count: u32 = self.returns.iter().sum();
+        if count < MIN_OBSERVATIONS {
+            return None;
+        }
+
+        let zone_height = field_height / ZONES as f32;
+        let sum: f32 = self
+            .returns
+            .iter()
+            .enumerate()
+            .map(|(zone, returns)| (zone as f32 + 0.5) * zone_height * *returns as f32)
+            .sum();
+        Some(sum / count as f32)
+    }
+
+    // Average change of the vertical ball speed by the returns of the player
+    pub fn expected_spin(&self) -> f32 {
+        match self.spin_count {
+            0 => 0.0,
+            count => self.spin_total / count as f32,
+        }
+    }
+
+    // Part of the balls the player missed at the given height, 0.5 without any observations
+    pub fn weakness(&self, y: f32, field_height: f32) -> f32 {
+        let zone = Self::zone(y, field_height);
+        (self.misses[zone] + 1) as f32 / (self.hits[zone] + self.misses[zone] + 2) as f32
+    }
+}
+
+/// Computer opponent, which moves the paddle of one player.
+/// It only sees the state of the simulation, like a human sees the screen,
+/// and predicts the path of the ball with the same rules as the simulation.
+/// On the highest level it is used as a benchmark for the rules of the game.
+#[derive(Debug, Clone)]
+pub struct Opponent {
+    player: Player,
+    skill: Skill,
+    /// Adjust the reaction time to the score, so that the matches stay close
+    dynamic: bool,
+    /// Direction of the ball the opponent reacted to, true if the ball comes towards the paddle
+    approaching: bool,
+    /// Remaining ticks until the opponent reacts to the current direction of the ball
+    reaction: u32,
+    /// Distance by which the opponent misjudges the current ball
+    error: f32,
+    /// Height of the ball above the paddle's centre, which was chosen to return the current ball
+    aim: Option<f32>,
+    /// What the opponent learned about the other player
+    habits: Habits,
+    /// Velocity of the ball and score of the opponent and the other player within the last tick,
+    /// to observe the habits
+    last_velocity: Vec2<f32>,
+    last_scores: (u8, u8),
+    /// Predicted height, where the last return of the opponent reaches the other player
+    return_arrival: Option<f32>,
+    /// Source of the misjudgements, seeded by the match to make them reproducible
+    rng: ChaCha8Rng,
+}
+
+impl Opponent {
+    // Constructor for an opponent, which knows nothing about the other player
+    // - dynamic adjusts the reaction time to the score
+    pub fn new(player: Player, difficulty: Difficulty, dynamic: bool, seed: u64) -> Self {
+        Self {
+            player,
+            skill: difficulty.skill(),
+            dynamic,
+            approaching: false,
+            reaction: 0,
+            error: 0.0,
+            aim: None,
+            habits: Habits::default(),
+            last_velocity: Vec2::zero(),
+            last_scores: (0, 0),
+            return_arrival: None,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    // Keep the habits of the other player, which were learned in a former match of the session
+    pub fn with_habits(mut self, habits: Habits) -> Self {
+        self.habits = habits;
+        self
+    }
+
+    // What the opponent learned about the other player
+    pub fn habits(&self) -> &Habits {
+        &self.habits
+    }
+
+    // Movement of the paddle within the next tick
+    // - the paddle waits in the centre while the ball moves away,
+    // and moves to the predicted point of arrival while the ball comes towards it
+    pub fn intent(&mut self, simulation: &Simulation) -> PaddleIntent {
+        if self.skill.adapts {
+            self.observe(simulation);
+        }
+
+        let ball = &simulation.ball;
+        let approaching = match self.player {
+            Player::One => ball.velocity.x < 0.0,
+            Player::Two => ball.velocity.x > 0.0,
+        };
+
+        // a new direction needs some time to react and will be misjudged a little bit
+        if approaching != self.approaching {
+            self.approaching = approaching;
+            self.reaction = self.reaction_ticks(simulation);
+            self.error = self.misjudgement(simulation);
+            self.aim = None;
+        }
+        if self.reaction > 0 {
+            self.reaction -= 1;
+            return PaddleIntent::default();
+        }
+
+        let paddle = simulation.paddle(self.player);
+        let field_height = simulation.field().y;
+        let arrival = trajectory::arrival(simulation, self.player, self.habits.expected_spin());
+        let target = match arrival {
+            Some(arrival) if approaching => {
+                // the aim will be chosen once, so that the paddle does not waver
+                let aim = match self.aim {
+                    Some(aim) => aim,
+                    None if self.skill.aims => *self.aim.insert(self.aim(simulation, &arrival)),
+                    None => 0.0,
+                };
+                let ball_y = ball.centre().y;
+                ball_y
+                    + (arrival.y - ball_y) * self.skill.prediction
+                    + self.error
+                    + aim * paddle.size.y
+            }
+            // wait between the expected return and the usual returns of the other player
+            Some(arrival) if self.skill.adapts => match self.habits.expected_return(field_height) {
+                Some(usual) => (arrival.y + usual) / 2.0,
+                None => field_height / 2.0,
+            },
+            Some(_) => field_height / 2.0,
+            // the ball already passed a paddle
+            None => ball.centre().y,
+        };
+
+        let intent = PaddleIntent::towards(
+            target - paddle.centre().y,
+            simulation.paddle_speed() * SIMULATION_TIMESTEP,
+        );
+        PaddleIntent {
+            axis: intent.axis * self.skill.speed,
+        }
+    }
+
+    // Ticks until the opponent reacts to a new direction of the ball
+    // - with a dynamic difficulty, the opponent gets slower while leading and faster while behind
+    fn reaction_ticks(&self, simulation: &Simulation) -> u32 {
+        let ticks = self.skill.reaction_ticks as i32;
+        if !self.dynamic {
+            return ticks as u32;
+        }
+
+        let lead =
+            simulation.score(self.player) as i32 - simulation.score(self.player.opponent()) as i32;
+        (ticks + lead * DYNAMIC_TICKS_PER_POINT).clamp(0, DYNAMIC_MAX_TICKS) as u32
+    }
+
+    // Learn from the last tick, where the other player returns the ball, how the player changes
+    // its direction and where the player misses it
+    fn observe(&mut self, simulation: &Simulation) {
+        let ball = &simulation.ball;
+        let field_height = simulation.field().y;
+        let towards_opponent = |velocity: Vec2<f32>| match self.player {
+            Player::One => velocity.x < 0.0,
+            Player::Two => velocity.x > 0.0,
+        };
+        let was_approaching = towards_opponent(self.last_velocity);
+        let approaching = towards_opponent(ball.velocity);
+        let scores = (
+            simulation.score(self.player),
+            simulation.score(self.player.opponent()),
+        );
+
+        if scores.0 > self.last_scores.0 {
+            // the other player missed the last return, the ball will be served again
+            if let Some(y) = self.return_arrival.take() {
+                self.habits.misses[Habits::zone(y, field_height)] += 1;
+            }
+        } else if scores.1 > self.last_scores.1 {
+            // the opponent missed the ball, which will be served again
+            self.return_arrival = None;
+        } else if approaching && !was_approaching && self.last_velocity.x != 0.0 {
+            // the other player returned the ball
+            if let Some(y) = self.return_arrival.take() {
+                self.habits.hits[Habits::zone(y, field_height)] += 1;
+            }
+            if let Some(arrival) = trajectory::arrival(simulation, self.player, 0.0) {
+                self.habits.returns[Habits::zone(arrival.y, field_height)] += 1;
+            }
+            self.habits.spin_total += ball.velocity.y - self.last_velocity.y;
+            self.habits.spin_count += 1;
+        } else if !approaching && was_approaching {
+            // the opponent returned the ball
+            self.return_arrival = trajectory::crossing(
+                ball,
+                trajectory::face_x(simulation, self.player.opponent()),
+                field_height,
+            )
+            .map(|crossing| crossing.y);
+        }
+
+        self.last_velocity = ball.velocity;
+        self.last_scores = scores;
+    }
+
+    // Choose the height of the ball above the paddle's centre, which sends the ball as far as
+    // possible away from the other paddle
+    // - only points, which the paddle can reach in time, will be tried
+    // - the other paddle is expected to move with full speed towards the returned ball
+    // - an adaptive opponent prefers the heights, where the other player often missed the ball
+    fn aim(&self, simulation: &Simulation, arrival: &Crossing) -> f32 {
+        let paddle = simulation.paddle(self.player);
+        let other = simulation.paddle(self.player.opponent());
+        let (highest, lowest) = simulation.paddle_centre_range(self.player);
+        let reach = simulation.paddle_speed() * arrival.time;
+        let field_height = simulation.field().y;
+        let other_face = trajectory::face_x(simulation, self.player.opponent());
+
+        (0..AIM_CANDIDATES)
+            .map(|index| AIM_RANGE * (index as f32 / (AIM_CANDIDATES - 1) as f32 - 0.5))
+            .filter(|offset| {
+                let centre = arrival.y + offset * paddle.size.y;
+                (highest..=lowest).contains(&centre) && (centre - paddle.centre().y).abs() <= reach
+            })
+            .filter_map(|offset| {
+                let ball = trajectory::returned(simulation, self.player, arrival, offset);
+                let crossing = trajectory::crossing(&ball, other_face, field_height)?;
+                let mut gap = (crossing.y - other.centre().y).abs()
+                    - simulation.paddle_speed() * (arrival.time + crossing.time);
+                if self.skill.adapts {
+                    gap += WEAKNESS_BONUS * self.habits.weakness(crossing.y, field_height);
+                }
+                Some((offset, gap))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(offset, _)| offset)
+            .unwrap_or(0.0)
+    }
+
+    // Distance by which the next ball will be misjudged
+    // - a mistake misses the ball by more than the half height of the paddle
+    fn misjudgement(&mut self, simulation: &Simulation) -> f32 {
+        let paddle_height = simulation.paddle(self.player).size.y;
+        let error = self.skill.aim_error;
+        let error = if error > 0.0 {
+            self.rng.random_range(-error..=error)
+        } else {
+            0.0
+        };
+
+        if self.rng.random_bool(self.skill.mistake_chance) {
+            (paddle_height + error.abs()) * error.signum()
+        } else {
+            error
+        }
+    }
+}
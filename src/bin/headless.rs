@@ -0,0 +1,270 @@
+// Headless Pong-Game: verifies replays and simulates matches between computer opponents
+// without a window and without SDL, e.g. to balance changes of the rules in a continuous integration.
+// All results will be printed as JSON to the standard output.
+
+use anyhow::{bail, Context};
+use clap::{Args, Parser, Subcommand};
+use pong_game::ai::{Difficulty, Opponent};
+use pong_game::config::Config;
+use pong_game::controller::{DeviceInput, PaddleController};
+use pong_game::replay::Replay;
+use pong_game::simulation::{GameMode, Player, Rules, Simulation};
+use pong_game::util::{BALL_SIZE, DEFAULT_UPDATE_RATE, PADDLE_SIZE, SIMULATION_TIMESTEP};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::path::PathBuf;
+use vek::Vec2;
+
+/// Longest simulated match in ticks by default, which is one hour of the match
+const MAX_TICKS: u64 = 60 * 60 * DEFAULT_UPDATE_RATE as u64;
+
+/// Verifies replays and simulates matches of the Pong-Game without a window.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Simulate a replay again and check that it ends with the recorded score
+    Verify {
+        /// Path of the replay file
+        #[arg(value_name = "PATH")]
+        replay: PathBuf,
+    },
+    /// Simulate matches between two computer opponents and print statistics
+    Simulate(SimulateArgs),
+}
+
+#[derive(Debug, Args)]
+struct SimulateArgs {
+    /// Number of matches
+    #[arg(short = 'n', long, default_value_t = 100)]
+    matches: u32,
+
+    /// Computer opponent of player 1
+    #[arg(long, value_enum, default_value_t = Difficulty::Hard)]
+    player_1: Difficulty,
+
+    /// Computer opponent of player 2
+    #[arg(long, value_enum, default_value_t = Difficulty::Hard)]
+    player_2: Difficulty,
+
+    /// Make the computer slower while it leads and faster while it's behind
+    #[arg(long)]
+    dynamic_difficulty: bool,
+
+    /// Game play mode of all matches
+    #[arg(short, long, value_enum, default_value_t = GameMode::Normal)]
+    mode: GameMode,
+
+    /// Configuration file with the rules and the size of the playfield [default: default rules]
+    #[arg(short, long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Maximum vertical speed a paddle adds to the ball at its edge
+    #[arg(long, value_name = "SPEED")]
+    paddle_spin: Option<f32>,
+
+    /// Horizontal speed the ball gains with every paddle hit
+    #[arg(long, value_name = "SPEED")]
+    ball_acc: Option<f32>,
+
+    /// Points a player needs to win a match
+    #[arg(long, value_name = "POINTS", value_parser = clap::value_parser!(u8).range(1..))]
+    score_limit: Option<u8>,
+
+    /// Seed of the simulation, each match gets its own seed from it [default: random]
+    #[arg(long, value_name = "NUMBER")]
+    seed: Option<u64>,
+
+    /// Longest match in ticks, a longer match ends without a winner
+    #[arg(long, value_name = "TICKS", default_value_t = MAX_TICKS)]
+    max_ticks: u64,
+}
+
+/// Result of the verification of a replay
+#[derive(Debug, Serialize)]
+struct Verification {
+    replay: PathBuf,
+    ticks: usize,
+    recorded_score: [u8; 2],
+    simulated_score: [u8; 2],
+    verified: bool,
+}
+
+/// Results of one player over all simulated matches
+#[derive(Debug, Serialize)]
+struct PlayerStatistics {
+    controller: Difficulty,
+    wins: u32,
+    win_rate: f64,
+}
+
+/// Results of all simulated matches
+#[derive(Debug, Serialize)]
+struct Statistics {
+    /// Seed of the simulation, which reproduces all matches
+    seed: u64,
+    rules: Rules,
+    matches: u32,
+    /// Matches, which reached the maximum number of ticks without a winner
+    unfinished: u32,
+    player_1: PlayerStatistics,
+    player_2: PlayerStatistics,
+    points: u64,
+    /// Paddle hits between the serve and the point, on average
+    average_rally_length: f64,
+    /// Highest speed of the ball in units per second
+    max_ball_speed: f32,
+    average_match_seconds: f64,
+}
+
+/// Running totals of the simulated matches
+#[derive(Debug, Default)]
+struct Totals {
+    wins: [u32; 2],
+    unfinished: u32,
+    points: u64,
+    hits: u64,
+    max_ball_speed: f32,
+    ticks: u64,
+}
+
+fn main() -> anyhow::Result<()> {
+    match Cli::parse().command {
+        Command::Verify { replay } => verify(replay),
+        Command::Simulate(args) => simulate(&args),
+    }
+}
+
+// Simulate the replay again and compare the score with the recorded one
+// - fails, if the scores differ
+fn verify(path: PathBuf) -> anyhow::Result<()> {
+    let replay = Replay::load(&path)?;
+    let simulation = replay.resimulate();
+    let score = [simulation.score(Player::One), simulation.score(Player::Two)];
+
+    let verification = Verification {
+        replay: path,
+        ticks: replay.tick_count(),
+        recorded_score: replay.final_score,
+        simulated_score: score,
+        verified: score == replay.final_score,
+    };
+    println!("{}", serde_json::to_string_pretty(&verification)?);
+
+    if !verification.verified {
+        bail!(
+            "The replay ends with {}:{} instead of the recorded score {}:{}",
+            score[0],
+            score[1],
+            replay.final_score[0],
+            replay.final_score[1]
+        );
+    }
+    Ok(())
+}
+
+// Simulate all matches and print the statistics
+fn simulate(args: &SimulateArgs) -> anyhow::Result<()> {
+    let mut config = match &args.config {
+        Some(path) => Config::read(path)?,
+        None => Config::default(),
+    };
+    if let Some(paddle_spin) = args.paddle_spin {
+        config.rules.paddle_spin = paddle_spin;
+    }
+    if let Some(ball_acc) = args.ball_acc {
+        config.rules.ball_acc = ball_acc;
+    }
+    if let Some(score_limit) = args.score_limit {
+        config.rules.score_limit = score_limit;
+    }
+    config.validate().context("Invalid command line option")?;
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut seeds = ChaCha8Rng::seed_from_u64(seed);
+    let mut totals = Totals::default();
+    for _ in 0..args.matches {
+        simulate_match(args, &config, seeds.random(), &mut totals);
+    }
+
+    let matches = args.matches;
+    let ratio = |part: f64, whole: f64| match whole > 0.0 {
+        true => part / whole,
+        false => 0.0,
+    };
+    let player = |controller: Difficulty, wins: u32| PlayerStatistics {
+        controller,
+        wins,
+        win_rate: ratio(wins as f64, matches as f64),
+    };
+    let statistics = Statistics {
+        seed,
+        rules: config.rules,
+        matches,
+        unfinished: totals.unfinished,
+        player_1: player(args.player_1, totals.wins[0]),
+        player_2: player(args.player_2, totals.wins[1]),
+        points: totals.points,
+        average_rally_length: ratio(totals.hits as f64, totals.points as f64),
+        max_ball_speed: totals.max_ball_speed,
+        average_match_seconds: ratio(totals.ticks as f64 / DEFAULT_UPDATE_RATE, matches as f64),
+    };
+    println!("{}", serde_json::to_string_pretty(&statistics)?);
+
+    Ok(())
+}
+
+// Simulate one match between the computer opponents and add its results to the totals
+// - the opponents are seeded by the match like in the game
+fn simulate_match(args: &SimulateArgs, config: &Config, seed: u64, totals: &mut Totals) {
+    let field = Vec2::new(config.window.width as f32, config.window.height as f32);
+    let mut simulation = Simulation::new(field, PADDLE_SIZE, BALL_SIZE, config.rules);
+    simulation.start(args.mode, seed);
+
+    let opponent = |player: Player, difficulty: Difficulty, offset: u64| {
+        let seed = seed.wrapping_add(offset);
+        Box::new(Opponent::new(
+            player,
+            difficulty,
+            args.dynamic_difficulty,
+            seed,
+        )) as Box<dyn PaddleController>
+    };
+    let mut controllers = [
+        opponent(Player::One, args.player_1, 1),
+        opponent(Player::Two, args.player_2, 2),
+    ];
+
+    // every change of the horizontal direction without a point is a paddle hit
+    let input = DeviceInput::default();
+    let mut direction = simulation.ball.velocity.x.signum();
+    for _ in 0..args.max_ticks {
+        let [player_1, player_2] = controllers
+            .each_mut()
+            .map(|controller| controller.intent(&simulation, &input));
+        let scored = simulation.step(SIMULATION_TIMESTEP, player_1, player_2);
+        totals.ticks += 1;
+
+        let velocity = simulation.ball.velocity;
+        totals.max_ball_speed = totals.max_ball_speed.max(velocity.magnitude());
+        match scored {
+            Some(_) => totals.points += 1,
+            None if velocity.x.signum() != direction => totals.hits += 1,
+            None => {}
+        }
+        direction = velocity.x.signum();
+
+        if let Some(winner) = simulation.winner() {
+            totals.wins[winner.index()] += 1;
+            return;
+        }
+    }
+
+    totals.unfinished += 1;
+}
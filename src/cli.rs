@@ -0,0 +1,94 @@
+use crate::config::{Config, DisplayMode};
+use crate::simulation::GameMode;
+use anyhow::Context;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// A Pong clone written in Rust.
+/// All options override the values of the configuration file.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// Path of the configuration file [default: config directory of the platform]
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Width of the window in pixels
+    #[arg(long, value_name = "PIXELS")]
+    pub width: Option<u32>,
+
+    /// Height of the window in pixels
+    #[arg(long, value_name = "PIXELS")]
+    pub height: Option<u32>,
+
+    /// Start the game in fullscreen mode, same as '--display-mode fullscreen'
+    #[arg(short, long, conflicts_with = "display_mode")]
+    pub fullscreen: bool,
+
+    /// How the game window covers the screen
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub display_mode: Option<DisplayMode>,
+
+    /// Language of all texts, e.g. 'en', 'de' or 'fr'
+    #[arg(short, long, value_name = "CODE")]
+    pub language: Option<String>,
+
+    /// Game play mode of the match, which will be started with '--start'
+    #[arg(short, long, value_enum, default_value_t = GameMode::Normal)]
+    pub difficulty: GameMode,
+
+    /// Points a player needs to win the match
+    #[arg(long, value_name = "POINTS", value_parser = clap::value_parser!(u8).range(1..))]
+    pub score_limit: Option<u8>,
+
+    /// Seed of the random number generator, to reproduce the serves of every match
+    #[arg(long, value_name = "NUMBER")]
+    pub seed: Option<u64>,
+
+    /// Skip the main menu and start a match immediately
+    #[arg(short, long)]
+    pub start: bool,
+
+    /// Watch the replay of a recorded match instead of playing
+    #[arg(short, long, value_name = "PATH", conflicts_with = "start")]
+    pub replay: Option<PathBuf>,
+}
+
+impl Cli {
+    // Path of the configuration file, which was given or the default one
+    pub fn config_path(&self) -> PathBuf {
+        self.config.clone().unwrap_or_else(Config::default_path)
+    }
+
+    // Game play mode of the match, which has to be started immediately
+    pub fn start_mode(&self) -> Option<GameMode> {
+        self.start.then_some(self.difficulty)
+    }
+
+    // Override the values of the configuration with the given options
+    pub fn apply(&self, config: &mut Config) -> anyhow::Result<()> {
+        if let Some(width) = self.width {
+            config.window.width = width;
+        }
+        if let Some(height) = self.height {
+            config.window.height = height;
+        }
+        if self.fullscreen {
+            config.display.mode = DisplayMode::Fullscreen;
+        }
+        if let Some(mode) = self.display_mode {
+            config.display.mode = mode;
+        }
+        if let Some(language) = &self.language {
+            config.language = language.clone();
+        }
+        if let Some(score_limit) = self.score_limit {
+            config.rules.score_limit = score_limit;
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+
+        config.validate().context("Invalid command line option")
+    }
+}
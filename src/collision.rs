@@ -0,0 +1,224 @@
+use crate::simulation::Body;
+use vek::Vec2;
+
+/// Tolerance for the time of impact, to detect hits on both faces of a corner at once
+const CORNER_TOLERANCE: f32 = 1e-4;
+
+/// Result of a swept collision test between a moving and a resting body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Time of impact, as a fraction between 0.0 and 1.0 of the tested movement
+    pub time: f32,
+    /// Normal of the face which was hit, pointing away from the resting body.
+    /// For a hit exactly on a corner both components are set.
+    pub normal: Vec2<f32>,
+}
+
+// Sweep the moving body along the displacement and check if it hits the resting body.
+// The resting body will be expanded by the size of the moving body, so that the test
+// becomes a ray cast of the moving body's position against the expanded rectangle.
+// - returns no contact if both bodies already overlap or if the moving body moves away
+pub fn sweep(moving: &Body, displacement: Vec2<f32>, resting: &Body) -> Option<Contact> {
+    let min = resting.position - moving.size;
+    let max = resting.position + resting.size;
+    let origin = moving.position;
+
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::zero();
+
+    for axis in 0..2 {
+        if displacement[axis] == 0.0 {
+            // no movement along this axis -> the bodies have to overlap on it all the time
+            if origin[axis] <= min[axis] || origin[axis] >= max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let t_min = (min[axis] - origin[axis]) / displacement[axis];
+        let t_max = (max[axis] - origin[axis]) / displacement[axis];
+        let (near, far) = if t_min < t_max {
+            (t_min, t_max)
+        } else {
+            (t_max, t_min)
+        };
+
+        if near > entry + CORNER_TOLERANCE {
+            entry = near;
+            normal = Vec2::zero();
+            normal[axis] = -displacement[axis].signum();
+        } else if near >= entry - CORNER_TOLERANCE {
+            // both faces of a corner will be hit at the same time
+            entry = entry.max(near);
+            normal[axis] = -displacement[axis].signum();
+        }
+        exit = exit.min(far);
+    }
+
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    Some(Contact {
+        time: entry,
+        normal,
+    })
+}
+
+// Calculate the shortest translation, which moves the first body out of the second one.
+// The normal of the contact points in the same direction as the translation.
+// - returns nothing if both bodies do not overlap
+pub fn penetration(moving: &Body, resting: &Body) -> Option<(Vec2<f32>, Vec2<f32>)> {
+    if !moving.intersects(resting) {
+        return None;
+    }
+
+    // overlap on each side of the resting body
+    let left = moving.position.x + moving.size.x - resting.position.x;
+    let right = resting.position.x + resting.size.x - moving.position.x;
+    let top = moving.position.y + moving.size.y - resting.position.y;
+    let bottom = resting.position.y + resting.size.y - moving.position.y;
+
+    let smallest = left.min(right).min(top).min(bottom);
+    let correction = if smallest == left {
+        Vec2::new(-left, 0.0)
+    } else if smallest == right {
+        Vec2::new(right, 0.0)
+    } else if smallest == top {
+        Vec2::new(0.0, -top)
+    } else {
+        Vec2::new(0.0, bottom)
+    };
+
+    Some((correction, normal_of(correction)))
+}
+
+// Check if the body left the playfield through the top or the bottom border
+// - returns the translation which moves the body back inside and the normal of the border
+pub fn border_penetration(body: &Body, field_height: f32) -> Option<(Vec2<f32>, Vec2<f32>)> {
+    let correction = if body.position.y < 0.0 {
+        // mirror the part of the movement which went through the top border
+        Vec2::new(0.0, -2.0 * body.position.y)
+    } else if body.position.y + body.size.y > field_height {
+        // mirror the part of the movement which went through the bottom border
+        Vec2::new(0.0, -2.0 * (body.position.y + body.size.y - field_height))
+    } else {
+        return None;
+    };
+
+    Some((correction, normal_of(correction)))
+}
+
+// Reflect the velocity along the normal of a contact.
+// Every component which moves into the face of the contact will be flipped,
+// so that a hit on a corner reflects both components.
+// - components which already move away from the face stay untouched
+pub fn reflect(velocity: Vec2<f32>, normal: Vec2<f32>) -> Vec2<f32> {
+    let mut reflected = velocity;
+    for axis in 0..2 {
+        if normal[axis] != 0.0 && velocity[axis] * normal[axis] < 0.0 {
+            reflected[axis] = -velocity[axis];
+        }
+    }
+
+    reflected
+}
+
+// Direction of a translation along both axes, whereby an axis without movement stays zero
+fn normal_of(translation: Vec2<f32>) -> Vec2<f32> {
+    translation.map(|component| {
+        if component == 0.0 {
+            0.0
+        } else {
+            component.signum()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Resting body with the given position and size
+    fn body(x: f32, y: f32, width: f32, height: f32) -> Body {
+        Body::new(Vec2::new(x, y), Vec2::new(width, height))
+    }
+
+    #[test]
+    fn sweep_on_corner_returns_both_normal_components() {
+        let ball = body(0.0, 0.0, 10.0, 10.0);
+        let paddle = body(20.0, 20.0, 10.0, 40.0);
+
+        let contact = sweep(&ball, Vec2::new(20.0, 20.0), &paddle).unwrap();
+        assert_eq!(contact.time, 0.5);
+        assert_eq!(contact.normal, Vec2::new(-1.0, -1.0));
+    }
+
+    #[test]
+    fn sweep_hits_top_face_of_paddle() {
+        let ball = body(5.0, 0.0, 10.0, 10.0);
+        let paddle = body(0.0, 20.0, 20.0, 40.0);
+
+        let contact = sweep(&ball, Vec2::new(0.0, 20.0), &paddle).unwrap();
+        assert_eq!(contact.time, 0.5);
+        assert_eq!(contact.normal, Vec2::new(0.0, -1.0));
+    }
+
+    #[test]
+    fn sweep_hits_bottom_face_of_paddle() {
+        let ball = body(5.0, 70.0, 10.0, 10.0);
+        let paddle = body(0.0, 20.0, 20.0, 40.0);
+
+        let contact = sweep(&ball, Vec2::new(0.0, -20.0), &paddle).unwrap();
+        assert_eq!(contact.time, 0.5);
+        assert_eq!(contact.normal, Vec2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn sweep_misses_paddle_when_moving_away() {
+        let ball = body(5.0, 0.0, 10.0, 10.0);
+        let paddle = body(0.0, 20.0, 20.0, 40.0);
+
+        assert_eq!(sweep(&ball, Vec2::new(0.0, -20.0), &paddle), None);
+    }
+
+    #[test]
+    fn penetration_uses_smallest_overlap() {
+        let ball = body(18.0, 25.0, 10.0, 10.0);
+        let paddle = body(20.0, 20.0, 20.0, 40.0);
+
+        let (correction, normal) = penetration(&ball, &paddle).unwrap();
+        assert_eq!(correction, Vec2::new(-8.0, 0.0));
+        assert_eq!(normal, Vec2::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn border_penetration_pushes_ball_back_inside() {
+        let mut ball = body(50.0, -4.0, 10.0, 10.0);
+        let (correction, normal) = border_penetration(&ball, 100.0).unwrap();
+        ball.position += correction;
+        assert_eq!(ball.position.y, 4.0);
+        assert_eq!(normal, Vec2::new(0.0, 1.0));
+
+        let mut ball = body(50.0, 95.0, 10.0, 10.0);
+        let (correction, normal) = border_penetration(&ball, 100.0).unwrap();
+        ball.position += correction;
+        assert_eq!(ball.position.y, 85.0);
+        assert_eq!(normal, Vec2::new(0.0, -1.0));
+
+        assert_eq!(border_penetration(&ball, 100.0), None);
+    }
+
+    #[test]
+    fn reflect_flips_only_components_moving_into_the_face() {
+        let normal = Vec2::new(-1.0, -1.0);
+        assert_eq!(reflect(Vec2::new(3.0, 2.0), normal), Vec2::new(-3.0, -2.0));
+        // the vertical component already moves away from the face
+        assert_eq!(reflect(Vec2::new(3.0, -2.0), normal), Vec2::new(-3.0, -2.0));
+        assert_eq!(
+            reflect(Vec2::new(3.0, -2.0), Vec2::new(0.0, 1.0)),
+            Vec2::new(3.0, 2.0)
+        );
+    }
+}
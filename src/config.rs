@@ -0,0 +1,691 @@
+use crate::action::Action;
+use crate::simulation::{Player, Rules};
+use crate::util::{
+    BACKGROUND_COLOR, DEMO_IDLE_TIMEOUT, FALLBACK_LANGUAGE, GAMEPAD_DEADZONE, GOAL_AREA_COLOR,
+    GOAL_AREA_WIDTH, LINE_COLOR, LINE_WIDTH, MAIN_MENU_FONT, MIN_FIELD_HEIGHT, MIN_FIELD_WIDTH,
+    MOUSE_SENSITIVITY, NET_DASH_LENGTH, NET_GAP_LENGTH, PONG_GAME_FONT, REPLAY_KEEP, WINDOW_HEIGHT,
+    WINDOW_WIDTH,
+};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file within the config directory of the platform
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Comment on top of a generated configuration file
+const CONFIG_FILE_HEADER: &str = "# Configuration of the Pong-Game
+# - delete this file to restore the default values
+# - all speeds are given in units per second
+# - display.mode is one of 'windowed', 'borderless' or 'fullscreen', whereby 'fullscreen' is the
+#   fullscreen mode of the desktop and keeps the resolution of the monitor like 'borderless'
+# - display.frame_limit = 0 means no limit of the frames per second
+# - all colors of the theme are given as [red, green, blue, alpha] from 0 to 255
+# - language is one of 'en', 'de' or 'fr', missing texts will be shown in English
+# - the keys of the controls are positions on the keyboard, named after a US layout,
+#   e.g. 'W' is the key right of 'Q' on every layout
+# - gamepads.player_1 and gamepads.player_2 are one of 'none', 'first' or 'second'
+# - players.player_1 and players.player_2 are one of 'human', 'keyboard', 'gamepad', 'mouse',
+#   'cpu_easy', 'cpu_normal', 'cpu_hard', 'cpu_adaptive', 'cpu_perfect' or 'external'
+# - 'human' combines the keyboard, the gamepad and the mouse, 'gamepad' uses the gamepad
+#   of gamepads.player_1 or gamepads.player_2
+# - 'external' runs the program of external.player_1 or external.player_2,
+#   e.g. player_1 = ['python3', 'bot.py'], see the README for its input and output
+# - players.dynamic_difficulty makes the computer slower while it leads and faster while it's behind
+# - demo.idle_timeout = 0 never ends an abandoned match
+# - replays.record stores every match in the directory 'replays' next to this file,
+#   only the newest replays.keep files will be kept, which must be at least 1,
+#   set replays.record = false to store no replays at all
+# - mouse.player is one of 'none', 'player_1' or 'player_2', mouse.relative captures the mouse
+# - add 'seed = <number>' on top of this file to reproduce the serves of every match,
+#   seeds above 9223372036854775807 have to be quoted like 'seed = \"18446744073709551615\"'
+
+";
+
+/// Size and mode of the game window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: WINDOW_WIDTH as u32,
+            height: WINDOW_HEIGHT as u32,
+        }
+    }
+}
+
+/// How the game window covers the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayMode {
+    /// Normal window with the configured size
+    Windowed,
+    /// Window without decorations, which covers the whole monitor
+    Borderless,
+    /// Fullscreen mode of the desktop, which keeps the resolution of the monitor.
+    /// tetra offers no exclusive fullscreen mode, which changes the resolution.
+    Fullscreen,
+}
+
+impl DisplayMode {
+    // Next mode in the order windowed -> borderless -> fullscreen -> windowed
+    pub fn next(self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+
+    // Previous mode in the order windowed -> borderless -> fullscreen -> windowed
+    pub fn previous(self) -> Self {
+        self.next().next()
+    }
+}
+
+/// Gamepad, which controls the paddle of a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GamepadSlot {
+    /// The player uses only the keyboard
+    None,
+    /// The first connected gamepad
+    First,
+    /// The second connected gamepad
+    Second,
+}
+
+impl GamepadSlot {
+    // Next slot in the order none -> first -> second -> none
+    pub fn next(self) -> Self {
+        match self {
+            GamepadSlot::None => GamepadSlot::First,
+            GamepadSlot::First => GamepadSlot::Second,
+            GamepadSlot::Second => GamepadSlot::None,
+        }
+    }
+
+    // Previous slot in the order none -> first -> second -> none
+    pub fn previous(self) -> Self {
+        self.next().next()
+    }
+}
+
+/// Assignment of the gamepads to the players and the settings of the sticks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamepadConfig {
+    pub player_1: GamepadSlot,
+    pub player_2: GamepadSlot,
+    /// Part of the range of a stick around its centre, which will be ignored
+    pub deadzone: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            player_1: GamepadSlot::First,
+            player_2: GamepadSlot::Second,
+            deadzone: GAMEPAD_DEADZONE,
+        }
+    }
+}
+
+/// Who moves the paddle of a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Controller {
+    /// Keyboard, gamepad or mouse
+    Human,
+    Keyboard,
+    Gamepad,
+    Mouse,
+    CpuEasy,
+    CpuNormal,
+    CpuHard,
+    /// Learns the habits of the other player
+    CpuAdaptive,
+    CpuPerfect,
+    /// Program, which gets the state of the match and answers with the movement of the paddle
+    External,
+}
+
+impl Controller {
+    // Next controller in the order human -> keyboard -> gamepad -> mouse -> easy -> ...
+    // -> perfect -> external -> human
+    pub fn next(self) -> Self {
+        match self {
+            Controller::Human => Controller::Keyboard,
+            Controller::Keyboard => Controller::Gamepad,
+            Controller::Gamepad => Controller::Mouse,
+            Controller::Mouse => Controller::CpuEasy,
+            Controller::CpuEasy => Controller::CpuNormal,
+            Controller::CpuNormal => Controller::CpuHard,
+            Controller::CpuHard => Controller::CpuAdaptive,
+            Controller::CpuAdaptive => Controller::CpuPerfect,
+            Controller::CpuPerfect => Controller::External,
+            Controller::External => Controller::Human,
+        }
+    }
+}
+
+/// Controllers of both paddles
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayersConfig {
+    pub player_1: Controller,
+    pub player_2: Controller,
+    /// Adjust the reaction time of the computer to the score, to keep the matches close
+    pub dynamic_difficulty: bool,
+}
+
+impl Default for PlayersConfig {
+    fn default() -> Self {
+        Self {
+            player_1: Controller::Human,
+            player_2: Controller::Human,
+            dynamic_difficulty: false,
+        }
+    }
+}
+
+impl PlayersConfig {
+    // Controller of the given player
+    pub fn controller(&self, player: Player) -> Controller {
+        match player {
+            Player::One => self.player_1,
+            Player::Two => self.player_2,
+        }
+    }
+}
+
+/// Programs, which move the paddles of the players with the 'external' controller
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExternalConfig {
+    /// Program and its arguments for player 1
+    pub player_1: Vec<String>,
+    /// Program and its arguments for player 2
+    pub player_2: Vec<String>,
+}
+
+impl ExternalConfig {
+    // Program and its arguments for the given player
+    pub fn command(&self, player: Player) -> &[String] {
+        match player {
+            Player::One => &self.player_1,
+            Player::Two => &self.player_2,
+        }
+    }
+}
+
+/// Demo match of two computer opponents behind the main menu
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DemoConfig {
+    /// Play the demo behind the main menu, otherwise the menu has an empty playfield
+    pub enabled: bool,
+    /// Seconds without any input during a match, until the game goes back to the main menu
+    pub idle_timeout: u32,
+}
+
+impl Default for DemoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_timeout: DEMO_IDLE_TIMEOUT,
+        }
+    }
+}
+
+/// Recording of the matches
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReplayConfig {
+    /// Store a replay of every match
+    pub record: bool,
+    /// Number of replay files, which will be kept, the oldest ones will be deleted.
+    /// At least the replay of the last match is kept.
+    pub keep: u32,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            record: true,
+            keep: REPLAY_KEEP,
+        }
+    }
+}
+
+/// Player, whose paddle follows the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MousePlayer {
+    /// The mouse is only used in the menus
+    None,
+    #[serde(rename = "player_1")]
+    Player1,
+    #[serde(rename = "player_2")]
+    Player2,
+}
+
+impl MousePlayer {
+    // Next player in the order none -> player 1 -> player 2 -> none
+    pub fn next(self) -> Self {
+        match self {
+            MousePlayer::None => MousePlayer::Player1,
+            MousePlayer::Player1 => MousePlayer::Player2,
+            MousePlayer::Player2 => MousePlayer::None,
+        }
+    }
+
+    // Previous player in the order none -> player 1 -> player 2 -> none
+    pub fn previous(self) -> Self {
+        self.next().next()
+    }
+
+    // Player of the simulation, if the mouse controls a paddle
+    pub fn player(self) -> Option<Player> {
+        match self {
+            MousePlayer::None => None,
+            MousePlayer::Player1 => Some(Player::One),
+            MousePlayer::Player2 => Some(Player::Two),
+        }
+    }
+}
+
+/// Control of a paddle by the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MouseConfig {
+    pub player: MousePlayer,
+    /// Capture the mouse during a match and move the paddle by the movement of the mouse,
+    /// instead of following the position of the cursor
+    pub relative: bool,
+    /// Distance the paddle follows a captured mouse per pixel of mouse movement
+    pub sensitivity: f32,
+}
+
+impl Default for MouseConfig {
+    fn default() -> Self {
+        Self {
+            player: MousePlayer::None,
+            relative: false,
+            sensitivity: MOUSE_SENSITIVITY,
+        }
+    }
+}
+
+/// Display settings, which can be changed at runtime in the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub mode: DisplayMode,
+    /// Synchronize the frames with the refresh rate of the monitor
+    pub vsync: bool,
+    /// Maximum number of frames per second, 0 means no limit
+    pub frame_limit: u32,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::Windowed,
+            vsync: true,
+            frame_limit: 0,
+        }
+    }
+}
+
+/// File names of the fonts within the resources directory, or absolute paths to other fonts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontConfig {
+    /// Font of the score
+    pub pong: String,
+    /// Font of the menus and messages
+    pub menu: String,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self {
+            pong: PONG_GAME_FONT.to_owned(),
+            menu: MAIN_MENU_FONT.to_owned(),
+        }
+    }
+}
+
+/// Colors and sizes of the playfield, all sizes are given in units of the playfield
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub background: [u8; 4],
+    /// Color of the net and the borders
+    pub lines: [u8; 4],
+    pub goal_areas: [u8; 4],
+    /// Width of the net and the borders
+    pub line_width: f32,
+    pub net_dash_length: f32,
+    pub net_gap_length: f32,
+    /// Width of the goal areas behind the paddles, 0 hides them
+    pub goal_area_width: f32,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            background: BACKGROUND_COLOR,
+            lines: LINE_COLOR,
+            goal_areas: GOAL_AREA_COLOR,
+            line_width: LINE_WIDTH,
+            net_dash_length: NET_DASH_LENGTH,
+            net_gap_length: NET_GAP_LENGTH,
+            goal_area_width: GOAL_AREA_WIDTH,
+        }
+    }
+}
+
+/// Settings of the game, which will be loaded from the configuration file at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Seed for every match, otherwise each match gets a new random seed
+    #[serde(skip_serializing_if = "Option::is_none", with = "seed_format")]
+    pub seed: Option<u64>,
+    /// Code of the language of all texts, e.g. 'en'
+    pub language: String,
+    pub window: WindowConfig,
+    pub display: DisplayConfig,
+    pub rules: Rules,
+    pub players: PlayersConfig,
+    pub external: ExternalConfig,
+    pub demo: DemoConfig,
+    pub replays: ReplayConfig,
+    pub fonts: FontConfig,
+    pub theme: ThemeConfig,
+    /// Physical key for each action, named after a US layout, e.g. 'W' or 'Backspace'
+    pub controls: BTreeMap<Action, String>,
+    pub gamepads: GamepadConfig,
+    pub mouse: MouseConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            language: FALLBACK_LANGUAGE.to_owned(),
+            window: WindowConfig::default(),
+            display: DisplayConfig::default(),
+            rules: Rules::default(),
+            players: PlayersConfig::default(),
+            external: ExternalConfig::default(),
+            demo: DemoConfig::default(),
+            replays: ReplayConfig::default(),
+            fonts: FontConfig::default(),
+            theme: ThemeConfig::default(),
+            controls: Action::default_bindings(),
+            gamepads: GamepadConfig::default(),
+            mouse: MouseConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    // Player, whose paddle follows the mouse
+    // - the 'mouse' controller takes the mouse, otherwise it joins the 'human' controller
+    // of mouse.player
+    pub fn mouse_player(&self) -> Option<Player> {
+        let players = self.players;
+        [Player::One, Player::Two]
+            .into_iter()
+            .find(|player| players.controller(*player) == Controller::Mouse)
+            .or_else(|| {
+                self.mouse
+                    .player
+                    .player()
+                    .filter(|player| players.controller(*player) == Controller::Human)
+            })
+    }
+
+    // Path of the configuration file within the config directory of the platform,
+    // e.g. '~/.config/pong-game/config.toml' on Linux
+    // - falls back to the current working directory, if the platform has no such directory
+    pub fn default_path() -> PathBuf {
+        match dirs::config_dir() {
+            Some(dir) => dir.join("pong-game").join(CONFIG_FILE_NAME),
+            None => PathBuf::from(CONFIG_FILE_NAME),
+        }
+    }
+
+    // Load the configuration from the given file.
+    // If the file does not exist, a file with the default values will be generated.
+    pub fn load(path: &Path) -> anyhow::Result<Config> {
+        if !path.exists() {
+            let config = Config::default();
+
+            // the game is playable without the file, so only report the problem
+            if let Err(err) = config.save(path) {
+                eprintln!("{:#}", err);
+            }
+
+            return Ok(config);
+        }
+
+        Config::read(path)
+    }
+
+    // Read the configuration from the given file without changing anything on the disk
+    // - fails, if the file does not exist
+    pub fn read(path: &Path) -> anyhow::Result<Config> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("Invalid config file '{}'", path.display()))?;
+
+        config
+            .validate()
+            .with_context(|| format!("Invalid config file '{}'", path.display()))?;
+
+        Ok(config)
+    }
+
+    // Write the configuration into the given file, missing directories will be created
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let content = toml::to_string_pretty(self).context("Failed to serialize the config")?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+        }
+
+        fs::write(path, format!("{}{}", CONFIG_FILE_HEADER, content))
+            .with_context(|| format!("Failed to write config file '{}'", path.display()))
+    }
+
+    // Change the settings within the given file and keep all other values of the file
+    // - the values of the command line will not be written into the file this way
+    pub fn update_file(path: &Path, change: impl FnOnce(&mut Config)) -> anyhow::Result<()> {
+        let mut config = Config::load(path)?;
+        change(&mut config);
+        config.save(path)
+    }
+
+    // Check all values, which can be parsed but make no sense for the game
+    // - the error names the key of the bad value
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if (self.window.width as f32) < MIN_FIELD_WIDTH {
+            bail!(
+                "'window.width' must be at least {}, got {}",
+                MIN_FIELD_WIDTH,
+                self.window.width
+            );
+        }
+        if (self.window.height as f32) < MIN_FIELD_HEIGHT {
+            bail!(
+                "'window.height' must be at least {}, got {}",
+                MIN_FIELD_HEIGHT,
+                self.window.height
+            );
+        }
+
+        if self.display.frame_limit != 0 && self.display.frame_limit < 10 {
+            bail!(
+                "'display.frame_limit' must be 0 or at least 10, got {}",
+                self.display.frame_limit
+            );
+        }
+
+        self.rules.validate()?;
+
+        if self.replays.keep < 1 {
+            bail!(
+                "'replays.keep' must be at least 1, got {}",
+                self.replays.keep
+            );
+        }
+
+        if !(0.0..1.0).contains(&self.gamepads.deadzone) {
+            bail!(
+                "'gamepads.deadzone' must be at least 0 and less than 1, got {}",
+                self.gamepads.deadzone
+            );
+        }
+
+        if !self.mouse.sensitivity.is_finite() || self.mouse.sensitivity <= 0.0 {
+            bail!(
+                "'mouse.sensitivity' must be greater than 0, got {}",
+                self.mouse.sensitivity
+            );
+        }
+
+        let sizes = [
+            ("theme.line_width", self.theme.line_width),
+            ("theme.net_dash_length", self.theme.net_dash_length),
+        ];
+        for (key, value) in sizes {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("'{}' must be greater than 0, got {}", key, value);
+            }
+        }
+
+        let spaces = [
+            ("theme.net_gap_length", self.theme.net_gap_length),
+            ("theme.goal_area_width", self.theme.goal_area_width),
+        ];
+        for (key, value) in spaces {
+            if !value.is_finite() || value < 0.0 {
+                bail!("'{}' must not be negative, got {}", key, value);
+            }
+        }
+
+        if self.language.is_empty() {
+            bail!("'language' must not be empty");
+        }
+
+        if self.fonts.pong.is_empty() {
+            bail!("'fonts.pong' must not be empty");
+        }
+        if self.fonts.menu.is_empty() {
+            bail!("'fonts.menu' must not be empty");
+        }
+
+        Ok(())
+    }
+}
+
+/// TOML only stores signed 64-bit integers, so seeds above `i64::MAX` are written as strings
+mod seed_format {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Seed as it is stored in the file
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Seed {
+        Number(i64),
+        Text(String),
+    }
+
+    // Write the seed as a number, if TOML can store it, otherwise as a string
+    pub fn serialize<S: Serializer>(seed: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        seed.map(|seed| match i64::try_from(seed) {
+            Ok(number) => Seed::Number(number),
+            Err(_) => Seed::Text(seed.to_string()),
+        })
+        .serialize(serializer)
+    }
+
+    // Read the seed from a non-negative number or from a string with a number
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        let seed = match Option::<Seed>::deserialize(deserializer)? {
+            Some(Seed::Number(number)) => u64::try_from(number).map_err(|_| {
+                serde::de::Error::custom(format!("'seed' must not be negative, got {}", number))
+            })?,
+            Some(Seed::Text(text)) => text.parse().map_err(|_| {
+                serde::de::Error::custom(format!("'seed' must be a number, got '{}'", text))
+            })?,
+            None => return Ok(None),
+        };
+
+        Ok(Some(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Write the configuration like `save` does and read it back like `read` does
+    fn round_trip(config: &Config) -> Config {
+        let content = toml::to_string_pretty(config).unwrap();
+        toml::from_str(&format!("{}{}", CONFIG_FILE_HEADER, content)).unwrap()
+    }
+
+    #[test]
+    fn seed_above_signed_range_survives_round_trip() {
+        for seed in [0, i64::MAX as u64, i64::MAX as u64 + 1, u64::MAX] {
+            let config = Config {
+                seed: Some(seed),
+                ..Config::default()
+            };
+            assert_eq!(round_trip(&config).seed, Some(seed));
+        }
+
+        assert_eq!(round_trip(&Config::default()).seed, None);
+    }
+
+    #[test]
+    fn validate_rejects_keeping_no_replays() {
+        let mut config = Config::default();
+        config.replays.keep = 0;
+        assert!(config.validate().is_err());
+
+        config.replays.keep = 1;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn seed_accepts_number_and_string() {
+        let config: Config = toml::from_str("seed = 42").unwrap();
+        assert_eq!(config.seed, Some(42));
+
+        let config: Config = toml::from_str("seed = \"18446744073709551615\"").unwrap();
+        assert_eq!(config.seed, Some(u64::MAX));
+
+        assert!(toml::from_str::<Config>("seed = -1").is_err());
+        assert!(toml::from_str::<Config>("seed = \"random\"").is_err());
+    }
+}
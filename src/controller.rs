@@ -0,0 +1,376 @@
+use crate::ai::{Difficulty, Habits, Opponent};
+use crate::config::{Config, Controller, GamepadSlot};
+use crate::simulation::{PaddleIntent, Player, Simulation};
+use crate::util::SIMULATION_TIMESTEP;
+use anyhow::{bail, Context};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// Input of the human players within one tick.
+/// The devices will be read once before each tick, so that the controllers do not need the window.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeviceInput {
+    /// Movement by the keys of player 1 and player 2
+    pub keys: [PaddleIntent; 2],
+    /// Movement by the gamepads in the first and the second slot
+    pub gamepads: [PaddleIntent; 2],
+    /// Height of the cursor on the playfield
+    pub cursor: f32,
+    /// Vertical movement of the captured mouse since the last tick, in pixels
+    pub mouse_movement: f32,
+}
+
+impl DeviceInput {
+    // Check if a player uses a device within this tick: a held key of a paddle, a gamepad stick
+    // outside the deadzone, a held d-pad or a movement of the captured mouse
+    // - the position of the cursor does not count, it stays the same without any input
+    pub fn is_active(&self) -> bool {
+        self.keys
+            .iter()
+            .chain(&self.gamepads)
+            .any(|intent| *intent != PaddleIntent::default())
+            || self.mouse_movement != 0.0
+    }
+}
+
+/// Anything that moves a paddle: an input device, a computer opponent or an external program.
+/// The controller will be asked once per tick for the movement of its paddle.
+pub trait PaddleController {
+    // Movement of the paddle within the next tick
+    fn intent(&mut self, simulation: &Simulation, input: &DeviceInput) -> PaddleIntent;
+
+    // Check if a human player moves the paddle, only a match of humans can be abandoned
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    // What a computer opponent learned about the other player during the match
+    fn habits(&self) -> Option<&Habits> {
+        None
+    }
+}
+
+/// Keys of a player
+pub struct KeyboardController {
+    player: Player,
+}
+
+impl KeyboardController {
+    pub fn new(player: Player) -> Self {
+        Self { player }
+    }
+}
+
+impl PaddleController for KeyboardController {
+    fn intent(&mut self, _simulation: &Simulation, input: &DeviceInput) -> PaddleIntent {
+        input.keys[self.player.index()]
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+}
+
+/// Stick and d-pad of the gamepad in a slot
+pub struct GamepadController {
+    slot: GamepadSlot,
+}
+
+impl PaddleController for GamepadController {
+    fn intent(&mut self, _simulation: &Simulation, input: &DeviceInput) -> PaddleIntent {
+        match self.slot {
+            GamepadSlot::None => PaddleIntent::default(),
+            GamepadSlot::First => input.gamepads[0],
+            GamepadSlot::Second => input.gamepads[1],
+        }
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+}
+
+/// The paddle follows either the cursor on the playfield or, while the mouse is captured,
+/// the movement of the mouse - but never faster than the paddle can move with the keyboard.
+pub struct MouseController {
+    player: Player,
+    /// Follow the movement of the captured mouse instead of the cursor
+    relative: bool,
+    /// Distance the paddle follows the captured mouse per pixel of mouse movement
+    sensitivity: f32,
+}
+
+impl PaddleController for MouseController {
+    fn intent(&mut self, simulation: &Simulation, input: &DeviceInput) -> PaddleIntent {
+        let paddle = simulation.paddle(self.player).centre().y;
+        let target = if self.relative {
+            paddle + input.mouse_movement * self.sensitivity
+        } else {
+            input.cursor
+        };
+
+        PaddleIntent::towards(
+            target - paddle,
+            simulation.paddle_speed() * SIMULATION_TIMESTEP,
+        )
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+}
+
+/// Several input devices, which can be used at the same time by one player
+pub struct HumanController {
+    devices: Vec<Box<dyn PaddleController>>,
+}
+
+impl PaddleController for HumanController {
+    fn intent(&mut self, simulation: &Simulation, input: &DeviceInput) -> PaddleIntent {
+        self.devices
+            .iter_mut()
+            .fold(PaddleIntent::default(), |intent, device| {
+                intent.combine(device.intent(simulation, input))
+            })
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+}
+
+impl PaddleController for Opponent {
+    fn intent(&mut self, simulation: &Simulation, _input: &DeviceInput) -> PaddleIntent {
+        Opponent::intent(self, simulation)
+    }
+
+    fn habits(&self) -> Option<&Habits> {
+        Some(Opponent::habits(self))
+    }
+}
+
+/// Program, which moves the paddle by a simple line based protocol:
+/// - on start it gets 'start <player> <field width> <field height>' on its standard input
+/// - before every tick it gets 'state <ball x> <ball y> <ball speed x> <ball speed y>
+///   <own paddle y> <other paddle y>' with the centres of the ball and the paddles
+/// - it answers with lines on its standard output, each with a number from -1.0 (up with full
+///   speed) to 1.0 (down with full speed)
+///
+/// The game never waits for the program: the paddle keeps the movement of the last answer,
+/// and a program which reads slowly only gets the latest state.
+pub struct ExternalController {
+    player: Player,
+    process: Child,
+    /// Next line for the program, which will be written in the background
+    outbox: Arc<Outbox>,
+    /// Answers of the program, which are read in the background
+    answers: Receiver<f32>,
+    intent: PaddleIntent,
+}
+
+impl ExternalController {
+    // Start the program of the given command line for the given player
+    pub fn spawn(
+        player: Player,
+        command: &[String],
+        simulation: &Simulation,
+    ) -> anyhow::Result<Self> {
+        let number = player.index() + 1;
+        let Some((program, args)) = command.split_first() else {
+            bail!("'external.player_{}' has no program to start", number);
+        };
+
+        let mut process = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start the external controller '{}'", program))?;
+        let mut input = process
+            .stdin
+            .take()
+            .context("The external controller has no standard input")?;
+        let output = process
+            .stdout
+            .take()
+            .context("The external controller has no standard output")?;
+
+        // a slow program must not stop the game, so the answers will be read by another thread
+        let (sender, answers) = mpsc::channel();
+        let name = program.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines().map_while(Result::ok) {
+                match line.trim().parse::<f32>() {
+                    Ok(axis) if axis.is_finite() => {
+                        if sender.send(axis).is_err() {
+                            break;
+                        }
+                    }
+                    _ => eprintln!(
+                        "The external controller '{}' answered '{}' instead of a number",
+                        name, line
+                    ),
+                }
+            }
+        });
+
+        // a program which reads slowly would block the game, so the lines will be written by
+        // another thread, which stops as soon as the program does not read anymore
+        // - the start line comes first and will never be skipped
+        let outbox = Arc::new(Outbox::default());
+        let field = simulation.field();
+        let start = format!("start {} {} {}", number, field.x, field.y);
+        let lines = Arc::clone(&outbox);
+        thread::spawn(move || {
+            let mut line = Some(start);
+            while let Some(next) = line {
+                if input.write_all(format!("{}\n", next).as_bytes()).is_err() {
+                    break;
+                }
+                line = lines.take();
+            }
+        });
+
+        Ok(Self {
+            player,
+            process,
+            outbox,
+            answers,
+            intent: PaddleIntent::default(),
+        })
+    }
+}
+
+/// Line for an external program, which has not been written yet.
+/// A new line replaces an unwritten one, so that a slow program skips the outdated states.
+#[derive(Default)]
+struct Outbox {
+    /// Unwritten line and whether the controller was dropped
+    state: Mutex<(Option<String>, bool)>,
+    changed: Condvar,
+}
+
+impl Outbox {
+    // Replace the unwritten line by the given one
+    fn put(&self, line: String) {
+        if let Ok(mut state) = self.state.lock() {
+            state.0 = Some(line);
+            self.changed.notify_one();
+        }
+    }
+
+    // Wait for the next line
+    // - returns nothing, once the controller was dropped
+    fn take(&self) -> Option<String> {
+        let mut state = self.state.lock().ok()?;
+        loop {
+            match &mut *state {
+                (_, true) => return None,
+                (line @ Some(_), false) => return line.take(),
+                (None, false) => state = self.changed.wait(state).ok()?,
+            }
+        }
+    }
+
+    // Stop the writing thread
+    fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.1 = true;
+            self.changed.notify_one();
+        }
+    }
+}
+
+impl PaddleController for ExternalController {
+    fn intent(&mut self, simulation: &Simulation, _input: &DeviceInput) -> PaddleIntent {
+        let ball = &simulation.ball;
+        let paddle = simulation.paddle(self.player).centre();
+        let other = simulation.paddle(self.player.opponent()).centre();
+        self.outbox.put(format!(
+            "state {} {} {} {} {} {}",
+            ball.centre().x,
+            ball.centre().y,
+            ball.velocity.x,
+            ball.velocity.y,
+            paddle.y,
+            other.y
+        ));
+
+        // only the latest answer counts
+        while let Ok(axis) = self.answers.try_recv() {
+            self.intent = PaddleIntent {
+                axis: axis.clamp(-1.0, 1.0),
+            };
+        }
+        self.intent
+    }
+}
+
+impl Drop for ExternalController {
+    fn drop(&mut self) {
+        // the program ends with the match
+        self.outbox.close();
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+// Controller of the given player as chosen in the configuration
+// - seed makes the mistakes of a computer opponent reproducible, habits is what it learned
+// about the other player in former matches
+// - fails only if an external program could not be started
+pub fn create(
+    player: Player,
+    config: &Config,
+    simulation: &Simulation,
+    seed: u64,
+    habits: Habits,
+) -> anyhow::Result<Box<dyn PaddleController>> {
+    let gamepad = match player {
+        Player::One => config.gamepads.player_1,
+        Player::Two => config.gamepads.player_2,
+    };
+    let mouse = MouseController {
+        player,
+        relative: config.mouse.relative,
+        sensitivity: config.mouse.sensitivity,
+    };
+
+    let opponent = |difficulty: Difficulty| -> Box<dyn PaddleController> {
+        Box::new(
+            Opponent::new(player, difficulty, config.players.dynamic_difficulty, seed)
+                .with_habits(habits.clone()),
+        )
+    };
+
+    let controller: Box<dyn PaddleController> = match config.players.controller(player) {
+        Controller::Human => {
+            let mut devices: Vec<Box<dyn PaddleController>> = vec![
+                Box::new(KeyboardController { player }),
+                Box::new(GamepadController { slot: gamepad }),
+            ];
+            if config.mouse_player() == Some(player) {
+                devices.push(Box::new(mouse));
+            }
+            Box::new(HumanController { devices })
+        }
+        Controller::Keyboard => Box::new(KeyboardController { player }),
+        Controller::Gamepad => Box::new(GamepadController { slot: gamepad }),
+        Controller::Mouse => Box::new(mouse),
+        Controller::External => Box::new(ExternalController::spawn(
+            player,
+            config.external.command(player),
+            simulation,
+        )?),
+        Controller::CpuEasy => opponent(Difficulty::Easy),
+        Controller::CpuNormal => opponent(Difficulty::Normal),
+        Controller::CpuHard => opponent(Difficulty::Hard),
+        Controller::CpuAdaptive => opponent(Difficulty::Adaptive),
+        Controller::CpuPerfect => opponent(Difficulty::Perfect),
+    };
+
+    Ok(controller)
+}
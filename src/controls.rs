@@ -0,0 +1,198 @@
+use crate::action::{Action, Scope};
+use anyhow::bail;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tetra::input::{self, GamepadButton, Key};
+use tetra::{Context, Event};
+
+/// Key bindings of all actions.
+/// The keys are physical positions on the keyboard, named after the labels of a US QWERTY layout,
+/// so that e.g. the paddle of the left player uses the same positions on every layout.
+/// Only the labels shown to the player depend on the layout of the keyboard.
+pub struct Controls {
+    keys: BTreeMap<Action, Key>,
+}
+
+impl Controls {
+    // Build the bindings from the names of the keys in the configuration
+    // - actions without a key get their default key
+    // - the error names the action of an unknown key or both actions of a conflict
+    pub fn new(config: &BTreeMap<Action, String>) -> anyhow::Result<Controls> {
+        let mut keys = BTreeMap::new();
+        for action in Action::ALL {
+            let name = config
+                .get(&action)
+                .map(String::as_str)
+                .unwrap_or_else(|| action.default_key());
+
+            let Some(key) = parse_key(name) else {
+                bail!(
+                    "'controls.{}' has the unknown key '{}'",
+                    action_name(action),
+                    name
+                );
+            };
+            keys.insert(action, key);
+        }
+
+        let controls = Controls { keys };
+        for (action, key) in &controls.keys {
+            if let Some(other) = controls.conflict(*action, *key) {
+                bail!(
+                    "'controls.{}' and 'controls.{}' must not use the same key '{:?}'",
+                    action_name(*action),
+                    action_name(other),
+                    key
+                );
+            }
+        }
+
+        Ok(controls)
+    }
+
+    // Names of the bound keys, to store them in the configuration
+    pub fn to_config(&self) -> BTreeMap<Action, String> {
+        self.keys
+            .iter()
+            .map(|(action, key)| (*action, format!("{:?}", key)))
+            .collect()
+    }
+
+    // Key which is bound to the action
+    pub fn key(&self, action: Action) -> Key {
+        self.keys[&action]
+    }
+
+    // Label of the key which is bound to the action within the current keyboard layout
+    pub fn key_name(&self, ctx: &Context, action: Action) -> String {
+        key_label(ctx, self.key(action))
+    }
+
+    // Check if the key of the action is held down
+    pub fn is_down(&self, ctx: &Context, action: Action) -> bool {
+        input::is_key_down(ctx, self.key(action))
+    }
+
+    // Action of a pressed key or gamepad button, which can be triggered in the given scope
+    pub fn pressed(&self, event: &Event, scope: Scope) -> Option<Action> {
+        match event {
+            Event::KeyPressed { key } => self
+                .keys
+                .iter()
+                .find(|(action, bound)| *bound == key && action.is_active_in(scope))
+                .map(|(action, _)| *action),
+            Event::GamepadButtonPressed { button, .. } => gamepad_action(*button, scope),
+            _ => None,
+        }
+    }
+
+    // Other action, which is bound to the key and can be triggered at the same time
+    pub fn conflict(&self, action: Action, key: Key) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(other, bound)| **bound == key && action.conflicts_with(**other))
+            .map(|(other, _)| *other)
+    }
+
+    // Bind the key to the action
+    // - returns the conflicting action and keeps the old key, if the key is already in use
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), Action> {
+        if let Some(other) = self.conflict(action, key) {
+            return Err(other);
+        }
+
+        self.keys.insert(action, key);
+        Ok(())
+    }
+}
+
+// Label of the key within the current keyboard layout, to show it to the player
+// - e.g. the physical key 'W' of a US layout will be shown as 'Z' on an AZERTY layout
+// - falls back to the name of the physical key, if the layout has no label for it
+pub fn key_label(ctx: &Context, key: Key) -> String {
+    match input::get_key_label(ctx, key) {
+        Some(label) => label.to_string(),
+        None => format!("{:?}", key),
+    }
+}
+
+// Action of a gamepad button, every gamepad can be used to navigate through the menus
+// - the buttons have a fixed layout: the d-pad navigates, 'A' confirms and 'B' goes back,
+// 'X' and 'Y' start a match, the shoulder buttons switch the controllers and 'Start' pauses a match
+fn gamepad_action(button: GamepadButton, scope: Scope) -> Option<Action> {
+    let action = match (scope, button) {
+        (Scope::Menu, GamepadButton::Up) => Action::MenuUp,
+        (Scope::Menu, GamepadButton::Down) => Action::MenuDown,
+        (Scope::Menu, GamepadButton::Left) => Action::MenuLeft,
+        (Scope::Menu, GamepadButton::Right) => Action::MenuRight,
+        (Scope::Menu, GamepadButton::A) => Action::Confirm,
+        (Scope::Menu, GamepadButton::B) => Action::Back,
+        (Scope::Menu, GamepadButton::X) => Action::StartNormal,
+        (Scope::Menu, GamepadButton::Y) => Action::StartHard,
+        (Scope::Menu, GamepadButton::Start) => Action::Settings,
+        (Scope::Menu, GamepadButton::LeftShoulder) => Action::SwitchPlayer1,
+        (Scope::Menu, GamepadButton::RightShoulder) => Action::SwitchPlayer2,
+        // the buttons of the paddles are not used to leave a running match by accident
+        (Scope::Match, GamepadButton::Start) => Action::Pause,
+        (Scope::Match, GamepadButton::Back) => Action::Back,
+        _ => return None,
+    };
+
+    Some(action)
+}
+
+// Physical key of the given name, e.g. 'W' or 'Backspace'
+fn parse_key(name: &str) -> Option<Key> {
+    let deserializer: StrDeserializer<ValueError> = name.into_deserializer();
+    Key::deserialize(deserializer).ok()
+}
+
+// Name of the action within the configuration file
+fn action_name(action: Action) -> String {
+    toml::Value::try_from(action)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .unwrap_or_else(|| format!("{:?}", action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_share_keys_between_menu_and_match() {
+        let controls = Controls::new(&Action::default_bindings()).unwrap();
+        assert_eq!(controls.key(Action::MenuUp), Key::Up);
+        assert_eq!(controls.key(Action::Player2Up), Key::Up);
+    }
+
+    #[test]
+    fn bind_rejects_conflicting_key_and_keeps_old_key() {
+        let mut controls = Controls::new(&Action::default_bindings()).unwrap();
+
+        assert_eq!(
+            controls.bind(Action::Player1Up, Key::Escape),
+            Err(Action::Quit)
+        );
+        assert_eq!(controls.key(Action::Player1Up), Key::W);
+
+        assert_eq!(
+            controls.bind(Action::Player1Up, Key::S),
+            Err(Action::Player1Down)
+        );
+        assert_eq!(controls.key(Action::Player1Up), Key::W);
+
+        // the menu is never shown while the paddles move
+        assert_eq!(controls.bind(Action::Player1Up, Key::Enter), Ok(()));
+        assert_eq!(controls.key(Action::Player1Up), Key::Enter);
+    }
+
+    #[test]
+    fn new_rejects_conflicting_config() {
+        let mut config = Action::default_bindings();
+        config.insert(Action::Player1Up, "Escape".to_owned());
+        assert!(Controls::new(&config).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use crate::config::{DisplayConfig, DisplayMode, WindowConfig};
+use anyhow::Context as anyhow_context;
+use std::thread;
+use std::time::{Duration, Instant};
+use tetra::window::{self, WindowPosition};
+use tetra::Context;
+
+// Apply the display settings to the window
+// - tetra's fullscreen mode keeps the resolution of the desktop,
+// the playfield will be scaled by the screen scaler in every mode
+pub fn apply(
+    ctx: &mut Context,
+    display: &DisplayConfig,
+    window: &WindowConfig,
+) -> anyhow::Result<()> {
+    // leave the fullscreen first, so that the window can be resized
+    if display.mode != DisplayMode::Fullscreen && window::is_fullscreen(ctx) {
+        window::set_fullscreen(ctx, false).context("Failed to leave the fullscreen mode")?;
+    }
+
+    match display.mode {
+        DisplayMode::Windowed => {
+            window::set_decorated(ctx, true);
+            window::set_size(ctx, window.width as i32, window.height as i32)
+                .context("Failed to resize the window")?;
+
+            let monitor = window::get_current_monitor(ctx).unwrap_or(0);
+            window::set_position(
+                ctx,
+                WindowPosition::Centered(monitor),
+                WindowPosition::Centered(monitor),
+            );
+        }
+        DisplayMode::Borderless => {
+            let (width, height) = window::get_current_monitor_size(ctx)
+                .context("Failed to get the size of the monitor")?;
+
+            window::set_decorated(ctx, false);
+            window::set_size(ctx, width, height).context("Failed to resize the window")?;
+
+            let monitor = window::get_current_monitor(ctx).unwrap_or(0);
+            window::set_position(
+                ctx,
+                WindowPosition::Centered(monitor),
+                WindowPosition::Centered(monitor),
+            );
+        }
+        DisplayMode::Fullscreen => {
+            if !window::is_fullscreen(ctx) {
+                window::set_fullscreen(ctx, true).context("Failed to enter the fullscreen mode")?;
+            }
+        }
+    }
+
+    if window::is_vsync_enabled(ctx) != display.vsync {
+        window::set_vsync(ctx, display.vsync).context("Failed to change the vsync mode")?;
+    }
+
+    Ok(())
+}
+
+/// Limits the number of frames per second, by waiting at the begin of each frame
+/// until the duration of a frame is over
+pub struct FrameLimiter {
+    last_frame: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new() -> Self {
+        Self {
+            last_frame: Instant::now(),
+        }
+    }
+
+    // Wait until the next frame may be drawn
+    // - a frame limit of 0 means no limit
+    pub fn wait(&mut self, frame_limit: u32) {
+        if frame_limit > 0 {
+            let frame_time = Duration::from_secs_f64(1.0 / frame_limit as f64);
+            let elapsed = self.last_frame.elapsed();
+            if elapsed < frame_time {
+                thread::sleep(frame_time - elapsed);
+            }
+        }
+
+        self.last_frame = Instant::now();
+    }
+}
@@ -1,285 +1,631 @@
+use crate::action::{Action, Scope};
+use crate::ai::Habits;
+use crate::config::{
+    Config, DisplayConfig, DisplayMode, GamepadConfig, GamepadSlot, MouseConfig, PlayersConfig,
+};
+use crate::controller::{self, DeviceInput, KeyboardController, PaddleController};
+use crate::controls::Controls;
+use crate::display::{self, FrameLimiter};
+use crate::gamepads::Gamepads;
+use crate::localization::Strings;
+use crate::mouse::Mouse;
+use crate::playfield::Playfield;
+use crate::replay::Replay;
+use crate::resources::ResourceLocator;
+use crate::scenes::{MainMenu, Playing, ReplayViewer, Scene, Transition};
+use crate::simulation::{GameMode, PaddleIntent, Player, Simulation};
 use crate::util::{
-    BALL_ACC, BALL_SPEED_HARD, BALL_SPEED_NORMAL, CENTER_LINE_SIZE, DASHED_MIDDLE_LINE,
-    DEFAULT_UPDATE_RATE, FREEZE_UPDATE_RATE, MAIN_MENU_FONT, MAIN_MENU_HEADER,
-    MAIN_MENU_HEADER_SIZE, MAIN_MENU_USAGE, MAIN_MENU_USAGE_SIZE, PADDLE_SPEED_HIGH,
-    PADDLE_SPEED_NORMAL, PADDLE_SPIN, PONG_GAME_FONT, SCORE_LIMIT, SCORE_TEXT_OFFSET,
-    SCORE_TEXT_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH, WIN_MESSAGE,
+    MAIN_MENU_HEADER_SIZE, MAIN_MENU_USAGE_SIZE, SCORE_TEXT_OFFSET, SCORE_TEXT_SIZE,
 };
-use rand::random;
+use anyhow::Context as anyhow_context;
+use std::path::PathBuf;
+use tetra::graphics::scaling::{ScalingMode, ScreenScaler};
 use tetra::graphics::text::{Font, Text};
-use tetra::graphics::{self, Color, Rectangle, Texture};
-use tetra::input::{self, Key};
+use tetra::graphics::{self, Color, Texture};
+use tetra::input::{Key, MouseButton};
 use tetra::math::Vec2;
-use tetra::{time, Context, Event, State};
-
-/// Interactive game structure to hold game objects
-struct SpriteEntity {
-    /// Sprite
-    sprite: Texture,
-    /// Position (2D coordinates - x and y)
-    position: Vec2<f32>,
-    /// velocity
-    speed: Vec2<f32>,
+use tetra::{time, window, Context, Event, State};
+
+/// Text object which will be drawn at a fixed position
+pub struct TextEntity {
+    pub text_to_display: Text,
+    pub position: Vec2<f32>,
 }
 
-impl SpriteEntity {
-    // Constructor for a game object without a speed
-    // - use the underscore to have no compiler warnings
-    fn _new(sprite: Texture, position: Vec2<f32>) -> Self {
+impl TextEntity {
+    // Constructor for normal text object
+    pub fn new(text_to_display: Text, pos: Vec2<f32>) -> Self {
         Self {
-            sprite,
-            position,
-            speed: Vec2::zero(),
+            text_to_display,
+            position: pos,
         }
     }
 
-    // Constructor for a game object that has a speed
-    fn with_velocity(sprite: Texture, position: Vec2<f32>, speed: Vec2<f32>) -> Self {
-        Self {
-            sprite,
-            position,
-            speed,
-        }
+    // Constructor for a text object, whose centre will be placed relative to the playfield
+    // - anchor is the relative position within the playfield, e.g. (0.5, 0.5) is the centre
+    pub fn anchored(
+        ctx: &mut Context,
+        mut text_to_display: Text,
+        field: Vec2<f32>,
+        anchor: Vec2<f32>,
+    ) -> Self {
+        let size = text_to_display
+            .get_bounds(ctx)
+            .map(|bounds| Vec2::new(bounds.width, bounds.height))
+            .unwrap_or_else(Vec2::zero);
+
+        Self::new(text_to_display, field * anchor - size / 2.0)
     }
 
-    // GETTER
-    fn width(&self) -> f32 {
-        self.sprite.width() as f32
+    pub fn draw(&mut self, ctx: &mut Context) {
+        self.text_to_display.draw(ctx, self.position);
     }
+}
 
-    fn height(&self) -> f32 {
-        self.sprite.height() as f32
-    }
+/// Space between the columns of a text table
+const COLUMN_GAP: f32 = 16.0;
 
-    fn bounds(&self) -> Rectangle {
-        Rectangle::new(
-            self.position.x,
-            self.position.y,
-            self.width(),
-            self.height(),
-        )
-    }
-
-    fn centre(&self) -> Vec2<f32> {
-        Vec2::new(
-            self.position.x + (self.width() / 2.0),
-            self.position.y + (self.height() / 2.0),
-        )
-    }
+/// One line of a text table
+pub enum TableLine {
+    /// Text over the whole width, e.g. a heading
+    Text(String),
+    /// Key in the first column and its description in the second column
+    Row(String, String),
+    Empty,
 }
 
-/// Game score structure
-struct TextEntity {
-    text_to_display: Text,
-    value: u8,
-    position: Vec2<f32>,
+/// Text with two columns, which will be aligned by the width of the widest key
+/// instead of spaces, so that the layout works with every font and language.
+/// Each line can be clicked like a button.
+pub struct TextTable {
+    keys: TextEntity,
+    descriptions: TextEntity,
+    /// Width and height of the whole table
+    size: Vec2<f32>,
+    line_height: f32,
+    line_count: usize,
 }
 
-impl TextEntity {
-    // Constructor for normal text object
-    fn new(text_to_display: Text, pos: Vec2<f32>) -> Self {
+impl TextTable {
+    // Constructor for a table, whose centre will be placed relative to the playfield
+    // - anchor is the relative position within the playfield, e.g. (0.5, 0.5) is the centre
+    pub fn anchored(
+        ctx: &mut Context,
+        lines: &[TableLine],
+        font: &Font,
+        field: Vec2<f32>,
+        anchor: Vec2<f32>,
+    ) -> Self {
+        // both columns are separate texts with the same number of lines
+        let (keys, descriptions): (Vec<&str>, Vec<&str>) = lines
+            .iter()
+            .map(|line| match line {
+                TableLine::Text(text) => (text.as_str(), ""),
+                TableLine::Row(key, description) => (key.as_str(), description.as_str()),
+                TableLine::Empty => ("", ""),
+            })
+            .unzip();
+        let mut keys = Text::new(keys.join("\n"), font.clone());
+        let mut descriptions = Text::new(descriptions.join("\n"), font.clone());
+
+        // the descriptions start behind the widest key, a text over the whole width is ignored
+        let key_width = lines
+            .iter()
+            .filter_map(|line| match line {
+                TableLine::Row(key, _) => Some(text_size(ctx, &mut Text::new(key, font.clone())).x),
+                _ => None,
+            })
+            .fold(0.0, f32::max);
+        let offset = Vec2::new(key_width + COLUMN_GAP, 0.0);
+
+        let keys_size = text_size(ctx, &mut keys);
+        let descriptions_size = text_size(ctx, &mut descriptions) + offset;
+        let size = Vec2::partial_max(keys_size, descriptions_size);
+        let position = field * anchor - size / 2.0;
+
+        // the distance between two lines is the difference between the heights of one and two lines
+        let line_height = text_size(ctx, &mut Text::new("|\n|", font.clone())).y
+            - text_size(ctx, &mut Text::new("|", font.clone())).y;
+
         Self {
-            text_to_display,
-            value: 0,
-            position: pos,
+            keys: TextEntity::new(keys, position),
+            descriptions: TextEntity::new(descriptions, position + offset),
+            size,
+            line_height,
+            line_count: lines.len(),
         }
     }
 
-    // Constructor for the game score
-    fn with_score_value(text_to_display: Text, value: u8, pos: Vec2<f32>) -> Self {
-        Self {
-            text_to_display,
-            value,
-            position: pos,
+    // Index of the line at the given position on the playfield, e.g. the line which was clicked
+    pub fn line_at(&self, position: Vec2<f32>) -> Option<usize> {
+        let relative = position - self.keys.position;
+        if relative.x < 0.0
+            || relative.x > self.size.x
+            || relative.y < 0.0
+            || self.line_height <= 0.0
+        {
+            return None;
         }
+
+        let index = (relative.y / self.line_height) as usize;
+        (index < self.line_count).then_some(index)
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context) {
+        self.keys.draw(ctx);
+        self.descriptions.draw(ctx);
     }
 }
 
-/// GameState object to hold all required things for the game.
-pub struct GameState {
-    player_1: SpriteEntity,
-    player_2: SpriteEntity,
-    score_player_1: TextEntity,
-    score_player_2: TextEntity,
-    ball: SpriteEntity,
-    center_line: TextEntity,
-    main_menu_header: TextEntity,
-    main_menu_usage: TextEntity,
-    winner_msg: TextEntity,
-    is_paused: bool,
-    is_main_menu_showing: bool,
-    is_to_end: bool,
+// Size of the area from the position of the text to the end of its last glyph
+fn text_size(ctx: &mut Context, text: &mut Text) -> Vec2<f32> {
+    text.get_bounds(ctx)
+        .map(|bounds| Vec2::new(bounds.right(), bounds.bottom()))
+        .unwrap_or_else(Vec2::zero)
 }
 
-impl GameState {
-    // constructor for the GameState struct
-    pub fn new(ctx: &mut Context) -> tetra::Result<GameState> {
-        // game starts for the first time -> so showing the main menu
-        let is_main_menu_showing = true;
+/// Resources which will be loaded once at startup and shared by all scenes
+pub struct Assets {
+    pub player_1_sprite: Texture,
+    pub player_2_sprite: Texture,
+    pub ball_sprite: Texture,
+    pub menu_header_font: Font,
+    pub menu_usage_font: Font,
+}
 
-        // Game is running
-        let is_paused = false;
+/// Data which is shared by all scenes: the resources, the running match and the playfield texts
+pub struct GameData {
+    pub assets: Assets,
+    /// All scenes draw onto the playfield with its logical resolution,
+    /// which will be scaled to the window size with black bars if necessary
+    pub scaler: ScreenScaler,
+    pub simulation: Simulation,
+    pub config: Config,
+    /// Texts in the language of the player
+    pub strings: Strings,
+    /// Keys of all actions
+    pub controls: Controls,
+    pub gamepads: Gamepads,
+    pub mouse: Mouse,
+    /// Controllers of the paddles of player 1 and player 2 in the running match
+    pub controllers: [Option<Box<dyn PaddleController>>; 2],
+    /// What the opponents of player 1 and player 2 learned in the former matches of the session
+    habits: [Habits; 2],
+    /// Recording of the running match
+    pub recording: Option<Replay>,
+    pub playfield: Playfield,
+    /// File, which stores the settings changed at runtime
+    config_path: PathBuf,
+    score_player_1: TextEntity,
+    score_player_2: TextEntity,
+}
 
-        // No player wins at this point, therefore the game is not to end
-        let is_to_end = false;
+impl GameData {
+    // constructor for the GameData struct
+    fn new(ctx: &mut Context, config: Config, config_path: PathBuf) -> anyhow::Result<GameData> {
+        let resources = ResourceLocator::new();
+
+        // load the paddle sprites for both players, the ball sprite and the fonts from the resources folder
+        let assets = Assets {
+            player_1_sprite: resources.texture(ctx, "player1.png")?,
+            player_2_sprite: resources.texture(ctx, "player2.png")?,
+            ball_sprite: resources.texture(ctx, "ball.png")?,
+            menu_header_font: resources.font(ctx, &config.fonts.menu, MAIN_MENU_HEADER_SIZE)?,
+            menu_usage_font: resources.font(ctx, &config.fonts.menu, MAIN_MENU_USAGE_SIZE)?,
+        };
+
+        // the logical resolution of the playfield is the configured window size,
+        // independent of the current size of the window
+        let field = Vec2::new(config.window.width as f32, config.window.height as f32);
+        let scaler = ScreenScaler::with_window_size(
+            ctx,
+            config.window.width as i32,
+            config.window.height as i32,
+            ScalingMode::ShowAll,
+        )?;
+
+        let strings = Strings::load(&resources, &config.language)?;
+        let controls = Controls::new(&config.controls)
+            .with_context(|| format!("Invalid config file '{}'", config_path.display()))?;
+
+        // net, borders and goal areas will be drawn procedurally to fit the playfield
+        let playfield = Playfield::new(ctx, field, &config.theme)?;
 
-        // create the main menu header style
-        let main_menu_header_text_style = Text::new(
-            MAIN_MENU_HEADER,
-            Font::vector(ctx, MAIN_MENU_FONT, MAIN_MENU_HEADER_SIZE)?,
+        // common Text for the game-score
+        let score_text_style = Text::new(
+            "0",
+            resources.font(ctx, &config.fonts.pong, SCORE_TEXT_SIZE)?,
         );
 
-        // create the main menu usage style
-        let main_menu_usage_text_style = Text::new(
-            MAIN_MENU_USAGE,
-            Font::vector(ctx, MAIN_MENU_FONT, MAIN_MENU_USAGE_SIZE)?,
+        // create the scores of player 1 and player 2
+        let [position_player_1, position_player_2] = score_positions(field);
+        let score_player_1 = TextEntity::new(score_text_style.clone(), position_player_1);
+        let score_player_2 = TextEntity::new(score_text_style, position_player_2);
+
+        // the simulation only needs to know the size of the sprites
+        // - paddles and ball will be centered on screen, the ball has zero speed
+        // until the player choose a game play mode (Normal or Hard)
+        let simulation = Simulation::new(
+            field,
+            Vec2::new(
+                assets.player_1_sprite.width() as f32,
+                assets.player_1_sprite.height() as f32,
+            ),
+            Vec2::new(
+                assets.ball_sprite.width() as f32,
+                assets.ball_sprite.height() as f32,
+            ),
+            config.rules,
         );
 
-        // create main menu header object
-        let main_menu_header = TextEntity::new(main_menu_header_text_style, Vec2::new(160.0, 40.0));
+        Ok(GameData {
+            assets,
+            scaler,
+            simulation,
+            config,
+            strings,
+            controls,
+            gamepads: Gamepads::new(),
+            mouse: Mouse::new(),
+            controllers: [None, None],
+            habits: [Habits::default(), Habits::default()],
+            recording: None,
+            playfield,
+            config_path,
+            score_player_1,
+            score_player_2,
+        })
+    }
 
-        // create the main menu usage object
-        let main_menu_usage = TextEntity::new(main_menu_usage_text_style, Vec2::new(160.0, 100.0));
+    // Apply the new display settings to the window and store them in the configuration file
+    // - the game keeps running with the old settings, if the platform refuses them
+    pub fn change_display(&mut self, ctx: &mut Context, display: DisplayConfig) {
+        self.config.display = display;
 
-        // create the dashed center line style
-        let center_line_text_style = Text::new(
-            DASHED_MIDDLE_LINE,
-            Font::vector(ctx, PONG_GAME_FONT, CENTER_LINE_SIZE)?,
-        );
+        if let Err(err) = display::apply(ctx, &self.config.display, &self.config.window) {
+            eprintln!("{:#}", err);
+        }
+        if let Err(err) = Config::update_file(&self.config_path, |config| config.display = display)
+        {
+            eprintln!("{:#}", err);
+        }
+    }
 
-        // create the center line text object
-        let center_line =
-            TextEntity::new(center_line_text_style, Vec2::new(WINDOW_WIDTH / 2.0, 0.0));
+    // Switch to the next display mode, e.g. from windowed to borderless
+    pub fn switch_display_mode(&mut self, ctx: &mut Context) {
+        let mut display = self.config.display;
+        display.mode = display.mode.next();
+        self.change_display(ctx, display);
+    }
 
-        // common Text for the game-score
-        let score_text_style = Text::new("0", Font::vector(ctx, PONG_GAME_FONT, SCORE_TEXT_SIZE)?);
+    // Assign the gamepads to the players and store the assignment in the configuration file
+    pub fn change_gamepads(&mut self, gamepads: GamepadConfig) {
+        self.config.gamepads = gamepads;
 
-        // create the winner msg style
-        let winner_msg_style = Text::new(
-            WIN_MESSAGE,
-            Font::vector(ctx, MAIN_MENU_FONT, MAIN_MENU_HEADER_SIZE)?,
-        );
+        if let Err(err) =
+            Config::update_file(&self.config_path, |config| config.gamepads = gamepads)
+        {
+            eprintln!("{:#}", err);
+        }
+    }
 
-        // create winner msg text object
-        let winner_msg = TextEntity::new(winner_msg_style, Vec2::zero());
+    // Change the controllers of the paddles and store them in the configuration file
+    // - takes effect with the next match
+    pub fn change_players(&mut self, players: PlayersConfig) {
+        self.config.players = players;
 
-        // create score player 1
-        let score_player_1 = TextEntity::with_score_value(
-            score_text_style.clone(),
-            0,
-            Vec2::new((WINDOW_WIDTH / 2.0) - 43.0, 16.0),
-        );
+        if let Err(err) = Config::update_file(&self.config_path, |config| config.players = players)
+        {
+            eprintln!("{:#}", err);
+        }
+    }
 
-        // load the paddle sprite for player 1 from the resources folder
-        let paddle_player_1 = Texture::new(ctx, "./resources/player1.png")?;
-        // pos player 1
-        let pos_player_1 = Vec2::new(
-            16.0,
-            (WINDOW_HEIGHT - paddle_player_1.height() as f32) / 2.0,
-        );
-        // create player 1 -> paddle has normal speed, if player choose game play mode 'HARD' it will be adjust
-        let player_1 = SpriteEntity::with_velocity(
-            paddle_player_1,
-            pos_player_1,
-            Vec2::new(0.0, PADDLE_SPEED_NORMAL),
-        );
+    // Change the control of a paddle by the mouse and store it in the configuration file
+    pub fn change_mouse(&mut self, mouse: MouseConfig) {
+        self.config.mouse = mouse;
 
-        // create score player 2
-        let score_player_2 = TextEntity::with_score_value(
-            score_text_style,
-            0,
-            Vec2::new(WINDOW_WIDTH / 2.0, 0.0) + SCORE_TEXT_OFFSET,
-        );
+        if let Err(err) = Config::update_file(&self.config_path, |config| config.mouse = mouse) {
+            eprintln!("{:#}", err);
+        }
+    }
 
-        // pos player 2
-        let paddle_player_2 = Texture::new(ctx, "./resources/player2.png")?;
-        let pos_player_2 = Vec2::new(
-            WINDOW_WIDTH - (paddle_player_2.width() as f32) - 16.0,
-            (WINDOW_HEIGHT - paddle_player_2.height() as f32) / 2.0,
-        );
+    // Load the texts of the new language and store it in the configuration file
+    // - all scenes have to be rebuilt afterwards to show the new texts
+    pub fn change_language(&mut self, language: &str) -> anyhow::Result<()> {
+        self.strings = Strings::load(&ResourceLocator::new(), language)?;
+        self.config.language = language.to_owned();
 
-        // create player 2
-        let player_2 = SpriteEntity::with_velocity(
-            paddle_player_2,
-            pos_player_2,
-            Vec2::new(0.0, PADDLE_SPEED_NORMAL),
-        );
+        if let Err(err) = Config::update_file(&self.config_path, |config| {
+            config.language = language.to_owned()
+        }) {
+            eprintln!("{:#}", err);
+        }
 
-        // create the ball sprite
-        let ball_sprite = Texture::new(ctx, "./resources/ball.png")?;
+        Ok(())
+    }
+
+    // Bind the key to the action and store the bindings in the configuration file
+    // - returns the conflicting action and keeps the old key, if the key is already in use
+    pub fn change_binding(&mut self, action: Action, key: Key) -> Result<(), Action> {
+        self.controls.bind(action, key)?;
+        self.config.controls = self.controls.to_config();
+        self.store_controls();
+
+        Ok(())
+    }
+
+    // Bind the default keys to all actions and store them in the configuration file
+    pub fn reset_bindings(&mut self) -> anyhow::Result<()> {
+        self.controls = Controls::new(&Action::default_bindings())?;
+        self.config.controls = self.controls.to_config();
+        self.store_controls();
+
+        Ok(())
+    }
+
+    // Store the current key bindings in the configuration file
+    fn store_controls(&self) {
+        let controls = self.config.controls.clone();
+        if let Err(err) =
+            Config::update_file(&self.config_path, |config| config.controls = controls)
+        {
+            eprintln!("{:#}", err);
+        }
+    }
+
+    // Position on the playfield, where the left mouse button was pressed
+    pub fn clicked(&self, ctx: &Context, event: &Event) -> Option<Vec2<f32>> {
+        match event {
+            Event::MouseButtonPressed {
+                button: MouseButton::Left,
+            } => Some(self.scaler.mouse_position(ctx)),
+            _ => None,
+        }
+    }
+
+    // Update the visible score text of the given player
+    pub fn update_score_text(&mut self, player: Player) {
+        let score = self.simulation.score(player).to_string();
+        match player {
+            Player::One => self.score_player_1.text_to_display.set_content(score),
+            Player::Two => self.score_player_2.text_to_display.set_content(score),
+        }
+    }
+
+    // Start a new match with the chosen game play mode
+    // - the seed will be logged, so that the serves and the computer opponents of the match
+    // can be reproduced
+    pub fn start_match(&mut self, mode: GameMode) {
+        self.save_replay();
+        let seed = self.config.seed.unwrap_or_else(rand::random);
+        self.simulation.start(mode, seed);
+        self.recording = self
+            .config
+            .replays
+            .record
+            .then(|| Replay::record(&self.simulation, mode));
+        self.update_score_text(Player::One);
+        self.update_score_text(Player::Two);
+
+        // each opponent gets its own sequence of mistakes
+        // and keeps what it learned about the other player during the session
+        // - an external program, which can't be started, will be replaced by the keyboard
+        self.release_controllers();
+        for (player, offset) in [(Player::One, 1), (Player::Two, 2)] {
+            let habits = self.habits[player.index()].clone();
+            let seed = seed.wrapping_add(offset);
+            let controller =
+                controller::create(player, &self.config, &self.simulation, seed, habits)
+                    .unwrap_or_else(|err| {
+                        eprintln!("{:#}", err);
+                        Box::new(KeyboardController::new(player))
+                    });
+            self.controllers[player.index()] = Some(controller);
+        }
 
-        // ball will be centered on screen
-        let ball_pos = Vec2::new(
-            (WINDOW_WIDTH - ball_sprite.width() as f32) / 2.0,
-            (WINDOW_HEIGHT - ball_sprite.height() as f32) / 2.0,
+        println!(
+            "Start match in mode {:?} with seed {}",
+            mode,
+            self.simulation.seed()
         );
+    }
 
-        // create the ball sprite -> with zero ball speed
-        // the ball speed will be set later, dependent on the user input (Normal or Hard)
-        let ball: SpriteEntity = SpriteEntity::with_velocity(ball_sprite, ball_pos, Vec2::zero());
+    // Read the input devices of the human players for the next tick
+    pub fn device_input(&mut self, ctx: &Context) -> DeviceInput {
+        let controls = &self.controls;
+        let keys = |up: Action, down: Action| {
+            PaddleIntent::from_buttons(controls.is_down(ctx, up), controls.is_down(ctx, down))
+        };
+        let deadzone = self.config.gamepads.deadzone;
+
+        DeviceInput {
+            keys: [
+                keys(Action::Player1Up, Action::Player1Down),
+                keys(Action::Player2Up, Action::Player2Down),
+            ],
+            gamepads: [
+                self.gamepads.intent(ctx, GamepadSlot::First, deadzone),
+                self.gamepads.intent(ctx, GamepadSlot::Second, deadzone),
+            ],
+            cursor: self.scaler.mouse_y(ctx),
+            mouse_movement: self.mouse.take_movement(),
+        }
+    }
 
-        Ok(GameState {
-            player_1,
-            player_2,
-            score_player_1,
-            score_player_2,
-            ball,
-            center_line,
-            main_menu_header,
-            main_menu_usage,
-            winner_msg,
-            is_paused,
-            is_main_menu_showing,
-            is_to_end,
-        })
+    // Directory of the replay files, next to the configuration file
+    pub fn replay_directory(&self) -> PathBuf {
+        self.config_path
+            .parent()
+            .map(|dir| dir.join("replays"))
+            .unwrap_or_else(|| PathBuf::from("replays"))
     }
 
-    // Method to check if the ball goes out of the screen and if this the case,
-    // increment the score counter for the right player and repositioning the ball.
-    fn score_checker(&mut self) {
-        // we increase the actual window width, to achieve a small delay until the ball comes into play
-        if self.ball.position.x > (WINDOW_WIDTH + 100.0) {
-            // Player 1 (left hand side) gets one point
-            self.score_player_1.value += 1;
-            self.score_player_1
-                .text_to_display
-                .set_content(self.score_player_1.value.to_string());
-
-            // repositioning of the ball
-            self.ball.position = Vec2::new(
-                (WINDOW_WIDTH - self.ball.width()) / 2.0,
-                (WINDOW_HEIGHT - self.ball.height()) / 2.0,
-            );
-
-            // move the ball randomly to player one or player two
-            match random() {
-                // make sign negative and move the ball to the left hand side
-                true => self.ball.speed = Vec2::new(-self.ball.speed.x, 0.0),
-                // reverse the sign and move the ball to the right hand side
-                false => self.ball.speed = Vec2::new(-(-self.ball.speed.x), 0.0),
+    // Store the recording of the match, which ended or was left, in the replay directory
+    // - the game keeps running without the replay, if the file can't be written
+    pub fn save_replay(&mut self) {
+        let Some(mut replay) = self.recording.take() else {
+            return;
+        };
+        if replay.tick_count() == 0 {
+            return;
+        }
+
+        replay.finish(&self.simulation);
+        let keep = self.config.replays.keep as usize;
+        match replay.save_in(&self.replay_directory(), keep) {
+            Ok(path) => println!("Saved replay '{}'", path.display()),
+            Err(err) => eprintln!("{:#}", err),
+        }
+    }
+
+    // Reset the score, the paddle position for both players and the ball
+    // - the recording of the left match will be saved
+    pub fn reset_match(&mut self) {
+        self.save_replay();
+        self.release_controllers();
+        self.simulation.reset();
+        self.update_score_text(Player::One);
+        self.update_score_text(Player::Two);
+    }
+
+    // Drop the controllers of the left match, so that external programs will be stopped
+    // - the habits learned by the opponents are kept for the next match
+    fn release_controllers(&mut self) {
+        for (controller, habits) in self.controllers.iter_mut().zip(&mut self.habits) {
+            if let Some(controller) = controller.take() {
+                if let Some(learned) = controller.habits() {
+                    *habits = learned.clone();
+                }
             }
-        } else if self.ball.position.x < -100.0 {
-            // Player 2 (right hand side) gets one point
-            self.score_player_2.value += 1;
-            self.score_player_2
-                .text_to_display
-                .set_content(self.score_player_2.value.to_string());
-
-            // repositioning of the ball
-            self.ball.position = Vec2::new(
-                (WINDOW_WIDTH - self.ball.width()) / 2.0,
-                (WINDOW_HEIGHT - self.ball.height()) / 2.0,
-            );
-
-            match random() {
-                true => self.ball.speed = Vec2::new(-self.ball.speed.x, 0.0),
-                false => self.ball.speed = Vec2::new(-(-self.ball.speed.x), 0.0),
+        }
+    }
+
+    // Fit the scaler, the markings and the scores to the playfield of the current simulation,
+    // e.g. for a replay, which was recorded with another window size
+    pub fn fit_field(&mut self, ctx: &mut Context) -> anyhow::Result<()> {
+        let field = self.simulation.field();
+        let (width, height) = (field.x as i32, field.y as i32);
+        if self.scaler.inner_size() == (width, height) {
+            return Ok(());
+        }
+
+        let (outer_width, outer_height) = self.scaler.outer_size();
+        self.scaler = ScreenScaler::new(
+            ctx,
+            width,
+            height,
+            outer_width,
+            outer_height,
+            ScalingMode::ShowAll,
+        )?;
+        self.playfield = Playfield::new(ctx, field, &self.config.theme)?;
+
+        let [position_player_1, position_player_2] = score_positions(field);
+        self.score_player_1.position = position_player_1;
+        self.score_player_2.position = position_player_2;
+
+        Ok(())
+    }
+
+    // Draw the markings of the playfield and the score of both players
+    pub fn draw_playfield(&mut self, ctx: &mut Context) {
+        self.playfield.draw(ctx);
+        self.score_player_1.draw(ctx);
+        self.score_player_2.draw(ctx);
+    }
+
+    // Draw the paddles and the ball
+    // - the positions will be interpolated between the last two ticks of the simulation,
+    // so that the movement looks smooth independent of the frame rate
+    pub fn draw_match_objects(&mut self, ctx: &mut Context) {
+        let blend = time::get_blend_factor(ctx);
+
+        self.assets
+            .ball_sprite
+            .draw(ctx, self.simulation.ball.interpolated_position(blend));
+        self.assets
+            .player_1_sprite
+            .draw(ctx, self.simulation.player_1.interpolated_position(blend));
+        self.assets
+            .player_2_sprite
+            .draw(ctx, self.simulation.player_2.interpolated_position(blend));
+    }
+}
+
+// Positions of the scores of player 1 and player 2 on both sides of the net
+fn score_positions(field: Vec2<f32>) -> [Vec2<f32>; 2] {
+    [
+        Vec2::new((field.x / 2.0) - 43.0, 16.0),
+        Vec2::new(field.x / 2.0, 0.0) + SCORE_TEXT_OFFSET,
+    ]
+}
+
+/// GameState object to hold all required things for the game.
+/// It drives a stack of scenes, whereby the scene on top of the stack is the active one.
+pub struct GameState {
+    data: GameData,
+    scenes: Vec<Box<dyn Scene>>,
+    frame_limiter: FrameLimiter,
+}
+
+impl GameState {
+    // constructor for the GameState struct
+    // - with a game play mode, the match starts immediately on top of the main menu
+    // - with a replay, the replay viewer will be shown on top of the main menu
+    pub fn new(
+        ctx: &mut Context,
+        config: Config,
+        config_path: PathBuf,
+        start_mode: Option<GameMode>,
+        replay: Option<Replay>,
+    ) -> anyhow::Result<GameState> {
+        let mut data = GameData::new(ctx, config, config_path)?;
+
+        // the window was created windowed or fullscreen, the borderless mode needs to be applied
+        // - the game stays playable in a window, so only report the problem
+        if let Err(err) = display::apply(ctx, &data.config.display, &data.config.window) {
+            eprintln!("{:#}", err);
+
+            data.config.display.mode = DisplayMode::Windowed;
+            if let Err(err) = display::apply(ctx, &data.config.display, &data.config.window) {
+                eprintln!("{:#}", err);
             }
         }
 
-        // check score limit -> if it's reached -> game ends
-        if self.score_player_1.value == SCORE_LIMIT || self.score_player_2.value == SCORE_LIMIT {
-            self.is_to_end = true;
+        // game starts for the first time -> so showing the main menu
+        let mut scenes: Vec<Box<dyn Scene>> = vec![Box::new(MainMenu::new(ctx, &data))];
+
+        if let Some(mode) = start_mode {
+            data.start_match(mode);
+            scenes.push(Box::new(Playing::new()));
+        }
+        if let Some(replay) = replay {
+            scenes.push(Box::new(ReplayViewer::new(ctx, &mut data, &replay)?));
+        }
+
+        Ok(GameState {
+            data,
+            scenes,
+            frame_limiter: FrameLimiter::new(),
+        })
+    }
+
+    // Apply the transition, which was requested by the active scene
+    fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scene) => {
+                self.scenes.clear();
+                self.scenes.push(scene);
+            }
         }
     }
 }
@@ -290,256 +636,80 @@ impl State<anyhow::Error> for GameState {
     // All implement methods in the trait 'State' will be automatically called from the game loop within the tetra::Context
     // by passing the GameState struct into the Context.run() method within the main function
     fn draw(&mut self, ctx: &mut tetra::Context) -> anyhow::Result<()> {
-        // we can implements here our own logic to draw the game screen or canvas
-        // if an error occurs, we will be able to use the anyhow::Error variant to
-        // make the error more readable
-
-        // First: clear always the screen and fill them with baby blue color
-        graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
-
-        // On game start, or if player pressed 'Backspace', show the main menu
-        if self.is_main_menu_showing {
-            self.main_menu_header
-                .text_to_display
-                .draw(ctx, self.main_menu_header.position);
-            self.main_menu_usage
-                .text_to_display
-                .draw(ctx, self.main_menu_usage.position);
-
-            // check if one player wins
-        } else if self.is_to_end {
-
-            // draw the dashed center line
-            self.center_line
-                .text_to_display
-                .draw(ctx, self.center_line.position);
-
-            // draw the endpoint status
-            self.score_player_1
-                .text_to_display
-                .draw(ctx, self.score_player_1.position);
-            self.score_player_2
-                .text_to_display
-                .draw(ctx, self.score_player_2.position);
-
-            // draw the winner message for the right player
-            if self.score_player_1.value == SCORE_LIMIT {
-                self.winner_msg
-                    .text_to_display
-                    .draw(ctx, Vec2::new((WINDOW_WIDTH / 2.0) - 300.0, 100.0));
-            } else if self.score_player_2.value == SCORE_LIMIT {
-                self.winner_msg
-                    .text_to_display
-                    .draw(ctx, Vec2::new((WINDOW_WIDTH / 2.0) + 25.0, 100.0));
-            }
-        } else {
-            // Otherwise start the game and draw the whole game context
-            self.ball.sprite.draw(ctx, self.ball.position);
-
-            // draw the dashed center line
-            self.center_line
-                .text_to_display
-                .draw(ctx, self.center_line.position);
-
-            // draw all required objects on screen
-            self.score_player_1
-                .text_to_display
-                .draw(ctx, self.score_player_1.position);
-            self.score_player_2
-                .text_to_display
-                .draw(ctx, self.score_player_2.position);
-            self.player_1.sprite.draw(ctx, self.player_1.position);
-            self.player_2.sprite.draw(ctx, self.player_2.position);
-        }
+        self.frame_limiter
+            .wait(self.data.config.display.frame_limit);
 
-        Ok(())
-    }
-
-    // Method is automatically called 60 times per second
-    // and updates the game objects on the screen
-    fn update(&mut self, ctx: &mut Context) -> Result<(), anyhow::Error> {
-        // Update game objects only if the game currently running
-        // that means no main menu is shown and no player win the game
-        if !self.is_main_menu_showing && !self.is_to_end {
-            // define game border (top and bottom)
-            let upper_game_limit: f32 = 10.0;
-            let lower_game_limit: f32 = (WINDOW_HEIGHT - self.player_1.sprite.height() as f32) - 10.0;
-
-            //Movement player 1
-            if input::is_key_down(ctx, Key::W) {
-                // move paddle up -> if the paddle is in the right range
-                if self.player_1.position.y > upper_game_limit {
-                    self.player_1.position.y -= self.player_1.speed.y;
-                }
-            }
+        // all scenes draw onto the canvas of the playfield
+        graphics::set_canvas(ctx, self.data.scaler.canvas());
 
-            if input::is_key_down(ctx, Key::S) {
-                // move paddle down
-                if self.player_1.position.y < lower_game_limit {
-                    self.player_1.position.y += self.player_1.speed.y;
-                }
-            }
+        // First: clear always the screen and fill them with the background color of the theme
+        graphics::clear(ctx, self.data.playfield.background());
 
-            // Movement player 2
-            if input::is_key_down(ctx, Key::Up) {
-                // move paddle up
-                if self.player_2.position.y > upper_game_limit {
-                    self.player_2.position.y -= self.player_2.speed.y;
-                }
-            }
+        // draw all visible scenes, beginning with the topmost scene which is not an overlay
+        let first_visible = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
 
-            if input::is_key_down(ctx, Key::Down) {
-                // move paddle down
-                if self.player_2.position.y < lower_game_limit {
-                    self.player_2.position.y += self.player_2.speed.y;
-                }
-            }
+        for scene in self.scenes[first_visible..].iter_mut() {
+            scene.draw(ctx, &mut self.data)?;
+        }
 
-            // move the ball on the screen with a certain speed
-            self.ball.position += self.ball.speed;
-
-            // get bounds of the game objects
-            let player1_bounds = self.player_1.bounds();
-            let player2_bounds = self.player_2.bounds();
-            let ball_bounds = self.ball.bounds();
-
-            // use the build-in method 'intersects' to check if the ball rectangle collides with
-            // one of the paddle rectangle
-            let paddle_hit = if ball_bounds.intersects(&player1_bounds) {
-                Some(&self.player_1)
-            } else if ball_bounds.intersects(&player2_bounds) {
-                Some(&self.player_2)
-            } else {
-                None
-            };
-
-            // when a collision occurred
-            if let Some(paddle) = paddle_hit {
-                // Increase the ball's velocity, then flip it.
-                self.ball.speed.x = -(self.ball.speed.x + (BALL_ACC * self.ball.speed.x.signum()));
-
-                // Calculate the offset between the paddle and the ball, as a number between
-                // -1.0 and 1.0.
-                let offset = (paddle.centre().y - self.ball.centre().y) / paddle.height();
-
-                // Apply the spin to the ball.
-                self.ball.speed.y += PADDLE_SPIN * -offset;
-            }
+        // scale the playfield to the window, the remaining space will be black
+        graphics::reset_canvas(ctx);
+        graphics::clear(ctx, Color::BLACK);
+        self.data.scaler.draw(ctx);
 
-            // check if the ball hit the top or the bottom of the screen
-            if self.ball.position.y <= 0.0
-                || self.ball.position.y + self.ball.height() >= WINDOW_HEIGHT
-            {
-                // if this is the case -> revert the ball direction
-                self.ball.speed.y = -self.ball.speed.y;
-            }
+        Ok(())
+    }
 
-            // check if the ball goes out of the screen and calculate the score
-            self.score_checker();
+    // Method is automatically called 60 times per second
+    // and updates the active scene
+    fn update(&mut self, ctx: &mut Context) -> Result<(), anyhow::Error> {
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.update(ctx, &mut self.data)?;
+            self.apply(transition);
         }
 
+        // the mouse will be captured only while it moves a paddle in a running match,
+        // so that the menus can be used with the cursor
+        let config = &self.data.config;
+        let captured = config.mouse.relative
+            && config.mouse_player().is_some()
+            && self
+                .scenes
+                .last()
+                .is_some_and(|scene| scene.captures_mouse());
+        self.data.mouse.capture(ctx, captured);
+
         Ok(())
     }
 
     // Called when a window or input event occurs
-    // Handle the keyboard events
-    fn event(&mut self, ctx: &mut Context, event: tetra::Event) -> Result<(), anyhow::Error> {
-        match event {
-            Event::KeyPressed { key: Key::P } => {
-                // it is only possible to pause the game, when the the main menu is not shown
-                if !self.is_main_menu_showing {
-                    // check if the game is already paused
-                    if !self.is_paused {
-                        // When user pressed 'P' reduce the update rate of the game from the game so that it freezes
-                        // --> default update rate is 60.0_f64
-                        time::set_timestep(ctx, time::Timestep::Fixed(FREEZE_UPDATE_RATE));
-                        self.is_paused = true;
-                    } else {
-                        // run the game loop again, set the default game update rate -> 60.0
-                        time::set_timestep(ctx, time::Timestep::Fixed(DEFAULT_UPDATE_RATE));
-                        self.is_paused = false;
-                    }
-                }
-            }
-            Event::KeyPressed { key: Key::N } => {
-                // it is only possible to set the game, when the main menu is shown
-                if self.is_main_menu_showing {
-                    // use method random() from rand crate to create a random bool to move the ball randomly to one of the two players
-                    match random() {
-                        // move the ball to the left hand side
-                        true => self.ball.speed = Vec2::new(-BALL_SPEED_NORMAL, 0.0),
-                        // otherwise move the ball to the right hand side
-                        false => self.ball.speed = Vec2::new(BALL_SPEED_NORMAL, 0.0),
-                    }
-                }
-                // Game starts -> main menu will be hide
-                self.is_main_menu_showing = false;
-                self.is_to_end = false;
-            }
-            Event::KeyPressed { key: Key::H } => {
-                if self.is_main_menu_showing {
-                    // adjust the paddle speed for both players, because the ball speed goes up
-                    self.player_1.speed = Vec2::new(0.0, PADDLE_SPEED_HIGH);
-                    self.player_2.speed = Vec2::new(0.0, PADDLE_SPEED_HIGH);
-
-                    match random() {
-                        true => self.ball.speed = Vec2::new(-BALL_SPEED_HARD, 0.0),
-                        false => self.ball.speed = Vec2::new(BALL_SPEED_HARD, 0.0),
-                    }
-                }
-                self.is_main_menu_showing = false;
-                self.is_to_end = false;
-            }
-            Event::KeyPressed {
-                key: Key::Backspace,
-            } => {
-                if !self.is_main_menu_showing {
-                    self.is_main_menu_showing = true;
-
-                    // reset the score value
-                    self.score_player_1.value = 0;
-                    self.score_player_2.value = 0;
-
-                    // reset the visibility score text
-                    self.score_player_1
-                        .text_to_display
-                        .set_content(self.score_player_1.value.to_string());
-                    self.score_player_2
-                        .text_to_display
-                        .set_content(self.score_player_2.value.to_string());
-
-                    // draw the cleaned score text again
-                    self.score_player_1
-                        .text_to_display
-                        .draw(ctx, self.score_player_1.position);
-                    self.score_player_2
-                        .text_to_display
-                        .draw(ctx, self.score_player_2.position);
-
-                    // reset the paddle position for both players
-                    self.player_1.position =
-                        Vec2::new(16.0, (WINDOW_HEIGHT - self.player_1.height()) / 2.0);
-
-                    self.player_2.position = Vec2::new(
-                        WINDOW_WIDTH - (self.player_2.width()) - 16.0,
-                        (WINDOW_HEIGHT - self.player_2.height()) / 2.0,
-                    );
-
-                    // draw the paddles again
-                    self.player_1.sprite.draw(ctx, self.player_1.position);
-                    self.player_2.sprite.draw(ctx, self.player_2.position);
-
-                    // reset the ball
-                    self.ball.position = Vec2::new(
-                        (WINDOW_WIDTH - self.ball.width()) / 2.0,
-                        (WINDOW_HEIGHT - self.ball.height()) / 2.0,
-                    );
-
-                    // draw the ball again
-                    self.ball.sprite.draw(ctx, self.ball.position);
+    // - the event will be passed to the active scene
+    fn event(&mut self, ctx: &mut Context, event: Event) -> Result<(), anyhow::Error> {
+        // keep the playfield fitted into the window
+        if let Event::Resized { width, height } = event {
+            self.data.scaler.set_outer_size(width, height);
+        }
+
+        // keep track of the connected gamepads for all scenes
+        self.data.gamepads.handle_event(&event);
+        self.data.mouse.handle_event(&event);
+
+        if let Some(scene) = self.scenes.last_mut() {
+            // actions of every scene, unless the scene waits for a key to bind
+            if !scene.captures_keys() {
+                match self.data.controls.pressed(&event, Scope::Global) {
+                    Some(Action::Quit) => window::quit(ctx),
+                    Some(Action::SwitchDisplayMode) => self.data.switch_display_mode(ctx),
+                    _ => {}
                 }
             }
-            _ => {}
+
+            let transition = scene.event(ctx, &mut self.data, event)?;
+            self.apply(transition);
         }
 
         Ok(())
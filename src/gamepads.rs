@@ -0,0 +1,70 @@
+use crate::config::GamepadSlot;
+use crate::simulation::PaddleIntent;
+use tetra::input::{self, GamepadAxis, GamepadButton};
+use tetra::{Context, Event};
+
+/// Keeps track of the connected gamepads, which can be assigned to the players.
+/// Each gamepad gets a fixed slot on connection, so that the players keep their gamepads
+/// while other gamepads will be connected or removed.
+pub struct Gamepads {
+    /// Id of the gamepad in the first and the second slot
+    slots: [Option<usize>; 2],
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Self { slots: [None; 2] }
+    }
+
+    // Update the slots, if a gamepad was connected or removed
+    // - a new gamepad takes the first free slot, so a reconnected gamepad gets its slot back
+    // - gamepads which are connected at startup will be reported as new ones, too
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::GamepadAdded { id } if !self.slots.contains(&Some(id)) => {
+                if let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) {
+                    *slot = Some(id);
+                }
+            }
+            Event::GamepadRemoved { id } => {
+                for slot in self.slots.iter_mut().filter(|slot| **slot == Some(id)) {
+                    *slot = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Id of the connected gamepad in the given slot
+    fn id(&self, slot: GamepadSlot) -> Option<usize> {
+        match slot {
+            GamepadSlot::None => None,
+            GamepadSlot::First => self.slots[0],
+            GamepadSlot::Second => self.slots[1],
+        }
+    }
+
+    // Check if a gamepad is connected in the given slot
+    pub fn is_connected(&self, slot: GamepadSlot) -> bool {
+        self.id(slot).is_some()
+    }
+
+    // Movement of the paddle by the gamepad in the given slot
+    // - the left stick moves the paddle proportional to its position, the d-pad with full speed
+    pub fn intent(&self, ctx: &Context, slot: GamepadSlot, deadzone: f32) -> PaddleIntent {
+        let Some(id) = self.id(slot) else {
+            return PaddleIntent::default();
+        };
+
+        let stick = PaddleIntent::from_axis(
+            input::get_gamepad_axis_position(ctx, id, GamepadAxis::LeftStickY),
+            deadzone,
+        );
+        let d_pad = PaddleIntent::from_buttons(
+            input::is_gamepad_button_down(ctx, id, GamepadButton::Up),
+            input::is_gamepad_button_down(ctx, id, GamepadButton::Down),
+        );
+
+        stick.combine(d_pad)
+    }
+}
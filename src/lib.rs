@@ -0,0 +1,13 @@
+// Core of the Pong-Game, which runs without a window: the simulation of a match,
+// the computer opponents, the controllers of the paddles, the replays and the configuration.
+// The game and the headless simulation (src/bin/headless.rs) are built on top of it.
+
+pub mod action;
+pub mod ai;
+pub mod collision;
+pub mod config;
+pub mod controller;
+pub mod replay;
+pub mod simulation;
+pub mod trajectory;
+pub mod util;
@@ -0,0 +1,91 @@
+use crate::resources::ResourceLocator;
+use crate::util::FALLBACK_LANGUAGE;
+use anyhow::Context;
+use std::collections::HashMap;
+
+/// Languages which are shipped with the game and can be chosen in the settings.
+/// Other languages can be used by adding a string table 'lang/<code>.toml' to the resources
+/// and setting the code in the configuration file.
+pub const LANGUAGES: &[&str] = &["en", "de", "fr"];
+
+/// String table of the fallback language, compiled into the binary
+const FALLBACK_TABLE: &str = include_str!("../resources/lang/en.toml");
+
+/// All player-facing strings of one language
+pub struct Strings {
+    language: String,
+    table: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Strings {
+    // Load the string table of the given language from the resources
+    // - a missing or broken table will be reported and the fallback language will be used
+    pub fn load(resources: &ResourceLocator, language: &str) -> anyhow::Result<Strings> {
+        let fallback =
+            parse(FALLBACK_TABLE).context("Invalid string table of the fallback language")?;
+
+        let table = if language == FALLBACK_LANGUAGE {
+            HashMap::new()
+        } else {
+            match resources
+                .text(&table_name(language))
+                .and_then(|content| parse(&content))
+            {
+                Ok(table) => table,
+                Err(err) => {
+                    eprintln!(
+                        "Failed to load language '{}', use '{}' instead: {:#}",
+                        language, FALLBACK_LANGUAGE, err
+                    );
+                    HashMap::new()
+                }
+            }
+        };
+
+        Ok(Strings {
+            language: language.to_owned(),
+            table,
+            fallback,
+        })
+    }
+
+    // Code of the language, e.g. 'en'
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    // Translated string of the given key
+    // - falls back to the fallback language and at last to the key itself,
+    // so that a missing string is visible but does not break the game
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.table
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+            .unwrap_or(key)
+    }
+}
+
+// Next or previous language of the shipped ones, starting from the given language
+// - an unknown language continues with the first one
+pub fn switch_language(language: &str, forward: bool) -> &'static str {
+    let count = LANGUAGES.len();
+    let index = match LANGUAGES.iter().position(|code| *code == language) {
+        Some(index) if forward => (index + 1) % count,
+        Some(index) => (index + count - 1) % count,
+        None => 0,
+    };
+
+    LANGUAGES[index]
+}
+
+// File name of the string table within the resources
+fn table_name(language: &str) -> String {
+    format!("lang/{}.toml", language)
+}
+
+// Parse a string table, which maps each key to the translated string
+fn parse(content: &str) -> anyhow::Result<HashMap<String, String>> {
+    toml::from_str(content).context("Invalid string table")
+}
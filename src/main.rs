@@ -4,32 +4,64 @@
 // Property to hide the console window only in the release builds only:
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use tetra::time::Timestep;
 use tetra::{Context, ContextBuilder};
 
 use anyhow::Context as anyhow_context;
+use clap::Parser;
 
+// the core of the game is shared with the headless simulation
+use pong_game::{action, ai, config, controller, replay, simulation, util};
+
+mod cli;
+mod controls;
+mod display;
 mod game;
-mod util;
+mod gamepads;
+mod localization;
+mod mouse;
+mod playfield;
+mod resources;
+mod scenes;
 
+use crate::cli::Cli;
+use crate::config::{Config, DisplayMode};
 use crate::game::GameState;
-use crate::util::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::replay::Replay;
+use crate::util::DEFAULT_UPDATE_RATE;
 
 fn main() -> anyhow::Result<()> {
+    // parse the command line, prints the help or the version and exits if requested
+    let cli = Cli::parse();
+
+    // load the settings of the game, a file with the default settings will be generated on first start
+    // - the options of the command line win over the config file
+    let config_path = cli.config_path();
+    let mut config = Config::load(&config_path)?;
+    cli.apply(&mut config)?;
+    let replay = cli.replay.as_deref().map(Replay::load).transpose()?;
+
     // create the game context object,
     // to hold all global state, such as manage window settings and connections
     // to the underlying graphics/audio/input hardware
-    let game_context: Result<Context, tetra::TetraError> =
-        ContextBuilder::new("Pong-Game", WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32)
-            .quit_on_escape(true)
-            .show_mouse(true)
-            .build();
+    let game_context: Result<Context, tetra::TetraError> = ContextBuilder::new(
+        "Pong-Game",
+        config.window.width as i32,
+        config.window.height as i32,
+    )
+    .show_mouse(true)
+    .resizable(true)
+    .fullscreen(config.display.mode == DisplayMode::Fullscreen)
+    .vsync(config.display.vsync)
+    .timestep(Timestep::Fixed(DEFAULT_UPDATE_RATE))
+    .build();
 
     match game_context {
         Ok(mut context) => {
             // main function will be return the result of method run()
             context.run(|ctx| {
                 // build the GameState object with all the required things for the game
-                let game_state = GameState::new(ctx)
+                let game_state = GameState::new(ctx, config, config_path, cli.start_mode(), replay)
                     .with_context(|| "Something went wrong while init the game.")?;
 
                 Ok(game_state)
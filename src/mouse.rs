@@ -0,0 +1,36 @@
+use tetra::{window, Context, Event};
+
+/// Movement of the mouse, which controls a paddle.
+/// While the mouse is captured the cursor is hidden and the paddle follows the movement of the
+/// mouse instead of the cursor.
+pub struct Mouse {
+    /// Vertical movement of the captured mouse since the last tick, in pixels
+    movement: f32,
+}
+
+impl Mouse {
+    pub fn new() -> Self {
+        Self { movement: 0.0 }
+    }
+
+    // Collect the movement of the mouse until the next tick
+    pub fn handle_event(&mut self, event: &Event) {
+        if let Event::MouseMoved { delta, .. } = event {
+            self.movement += delta.y;
+        }
+    }
+
+    // Capture or release the mouse, the cursor will be hidden while the mouse is captured
+    // - the movement before the capture will be forgotten, so that the paddle does not jump
+    pub fn capture(&mut self, ctx: &mut Context, captured: bool) {
+        if window::is_relative_mouse_mode(ctx) != captured {
+            window::set_relative_mouse_mode(ctx, captured);
+            self.movement = 0.0;
+        }
+    }
+
+    // Vertical movement of the mouse since the last tick
+    pub fn take_movement(&mut self) -> f32 {
+        std::mem::take(&mut self.movement)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::config::ThemeConfig;
+use tetra::graphics::mesh::{GeometryBuilder, Mesh, ShapeStyle};
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+use tetra::Context;
+
+/// Markings of the playfield: the dashed net, the top and bottom borders and the goal areas.
+/// All markings will be built once as a single mesh, sized from the dimensions of the playfield.
+pub struct Playfield {
+    background: Color,
+    markings: Mesh,
+    /// Rectangle over the whole playfield, to dim everything below a menu
+    shade: Mesh,
+}
+
+impl Playfield {
+    // constructor which builds the markings for a playfield of the given size
+    pub fn new(ctx: &mut Context, field: Vec2<f32>, theme: &ThemeConfig) -> tetra::Result<Self> {
+        let mut geometry = GeometryBuilder::new();
+
+        // goal areas behind the paddles on both sides
+        if theme.goal_area_width > 0.0 {
+            geometry.set_color(color(theme.goal_areas));
+            geometry.rectangle(
+                ShapeStyle::Fill,
+                Rectangle::new(0.0, 0.0, theme.goal_area_width, field.y),
+            )?;
+            geometry.rectangle(
+                ShapeStyle::Fill,
+                Rectangle::new(
+                    field.x - theme.goal_area_width,
+                    0.0,
+                    theme.goal_area_width,
+                    field.y,
+                ),
+            )?;
+        }
+
+        geometry.set_color(color(theme.lines));
+
+        // borders at the top and the bottom, where the ball bounces off
+        geometry.rectangle(
+            ShapeStyle::Fill,
+            Rectangle::new(0.0, 0.0, field.x, theme.line_width),
+        )?;
+        geometry.rectangle(
+            ShapeStyle::Fill,
+            Rectangle::new(0.0, field.y - theme.line_width, field.x, theme.line_width),
+        )?;
+
+        // dashed net in the middle, the dashes will be spread over the whole height
+        let net_x = (field.x - theme.line_width) / 2.0;
+        let mut y = 0.0;
+        while y < field.y {
+            let length = theme.net_dash_length.min(field.y - y);
+            geometry.rectangle(
+                ShapeStyle::Fill,
+                Rectangle::new(net_x, y, theme.line_width, length),
+            )?;
+            y += theme.net_dash_length + theme.net_gap_length;
+        }
+
+        let shade = Mesh::rectangle(
+            ctx,
+            ShapeStyle::Fill,
+            Rectangle::new(0.0, 0.0, field.x, field.y),
+        )?;
+
+        Ok(Self {
+            background: color(theme.background),
+            markings: geometry.build_mesh(ctx)?,
+            shade,
+        })
+    }
+
+    // Color to clear the screen with, before anything else will be drawn
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    // Draw the net, the borders and the goal areas
+    pub fn draw(&self, ctx: &mut Context) {
+        self.markings.draw(ctx, DrawParams::new());
+    }
+
+    // Dim everything, which was drawn before
+    // - opacity is the part of the black, from 0.0 for nothing to 1.0 for a black playfield
+    pub fn draw_shade(&self, ctx: &mut Context, opacity: f32) {
+        self.shade.draw(
+            ctx,
+            DrawParams::new().color(Color::rgba(0.0, 0.0, 0.0, opacity)),
+        );
+    }
+}
+
+// Convert a RGBA color of the configuration file
+fn color([r, g, b, a]: [u8; 4]) -> Color {
+    Color::rgba8(r, g, b, a)
+}
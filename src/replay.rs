@@ -0,0 +1,519 @@
+use crate::controller::{DeviceInput, PaddleController};
+use crate::simulation::{GameMode, PaddleIntent, Player, Rules, Simulation};
+use crate::util::{DEFAULT_UPDATE_RATE, MIN_FIELD_HEIGHT, MIN_FIELD_WIDTH, SIMULATION_TIMESTEP};
+use anyhow::{bail, Context};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use vek::Vec2;
+
+/// First bytes of every replay file
+const MAGIC: &[u8; 8] = b"PONGRPLY";
+
+/// Version of the file format, which has to be increased with every incompatible change
+const VERSION: u16 = 1;
+
+/// Extension of the replay files
+const EXTENSION: &str = "pongreplay";
+
+/// Steps of the recorded paddle movement in each direction, the movement will be rounded to
+/// these steps before the tick, so that the match and its replay use exactly the same input
+const INTENT_STEPS: f32 = 127.0;
+
+/// Most ticks with the same input, which are stored as one run
+const MAX_RUN: u16 = u16::MAX;
+
+/// Longest replay in ticks, which is one day of a match, so that a broken file
+/// cannot fill the memory with its runs
+pub const MAX_TICKS: usize = 24 * 60 * 60 * DEFAULT_UPDATE_RATE as usize;
+
+/// Recording of a match, which reproduces the match tick by tick.
+/// The simulation is deterministic, so the seed, the rules, the sizes of the playfield and the
+/// input of both players in every tick are enough to get exactly the same ball path again.
+///
+/// The file stores the input as runs of ticks with the same input of both players:
+/// - magic 'PONGRPLY', version (u16)
+/// - seed (u64), game play mode (u8: 0 normal, 1 hard)
+/// - size of the field, the paddles and the ball (6 x f32)
+/// - rules: speeds, spin and acceleration (6 x f32), score limit (u8)
+/// - final score of player 1 and player 2 (2 x u8)
+/// - number of runs (u32), each run with its ticks (u16) and the input of both players (2 x i8)
+///
+/// All numbers are stored in little endian byte order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub rules: Rules,
+    pub field: Vec2<f32>,
+    pub paddle_size: Vec2<f32>,
+    pub ball_size: Vec2<f32>,
+    /// Score of player 1 and player 2 at the end of the recording
+    pub final_score: [u8; 2],
+    /// Input of player 1 and player 2 in every tick, from -INTENT_STEPS up to INTENT_STEPS down
+    inputs: Vec<[i8; 2]>,
+}
+
+impl Replay {
+    // Start the recording of the match, which was just started in the simulation
+    pub fn record(simulation: &Simulation, mode: GameMode) -> Self {
+        Self {
+            seed: simulation.seed(),
+            mode,
+            rules: *simulation.rules(),
+            field: simulation.field(),
+            paddle_size: simulation.player_1.size,
+            ball_size: simulation.ball.size,
+            final_score: [0, 0],
+            inputs: Vec::new(),
+        }
+    }
+
+    // Add the input of both players for the next tick
+    // - returns the input as it was recorded, which has to be used for the tick
+    pub fn push(&mut self, intents: [PaddleIntent; 2]) -> [PaddleIntent; 2] {
+        let input = intents.map(quantize);
+        self.inputs.push(input);
+        input.map(dequantize)
+    }
+
+    // Remember the score at the end of the recording
+    pub fn finish(&mut self, simulation: &Simulation) {
+        self.final_score = [simulation.score(Player::One), simulation.score(Player::Two)];
+    }
+
+    // Number of recorded ticks
+    pub fn tick_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    // New simulation in the state at the start of the recorded match
+    pub fn simulation(&self) -> Simulation {
+        let mut simulation =
+            Simulation::new(self.field, self.paddle_size, self.ball_size, self.rules);
+        simulation.start(self.mode, self.seed);
+        simulation
+    }
+
+    // Simulate the whole recorded match again, e.g. to verify its final score
+    pub fn resimulate(&self) -> Simulation {
+        let mut simulation = self.simulation();
+        let mut playback = [self.playback(Player::One), self.playback(Player::Two)];
+        let input = DeviceInput::default();
+        for _ in 0..self.tick_count() {
+            let [player_1, player_2] = playback
+                .each_mut()
+                .map(|playback| playback.intent(&simulation, &input));
+            simulation.step(SIMULATION_TIMESTEP, player_1, player_2);
+        }
+
+        simulation
+    }
+
+    // Controller, which plays back the recorded input of the given player
+    pub fn playback(&self, player: Player) -> PlaybackController {
+        PlaybackController {
+            intents: self
+                .inputs
+                .iter()
+                .map(|input| dequantize(input[player.index()]))
+                .collect(),
+            tick: 0,
+        }
+    }
+
+    // Bytes of the replay file
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(match self.mode {
+            GameMode::Normal => 0,
+            GameMode::Hard => 1,
+        });
+
+        let rules = &self.rules;
+        let floats = [
+            self.field.x,
+            self.field.y,
+            self.paddle_size.x,
+            self.paddle_size.y,
+            self.ball_size.x,
+            self.ball_size.y,
+            rules.paddle_speed_normal,
+            rules.paddle_speed_hard,
+            rules.ball_speed_normal,
+            rules.ball_speed_hard,
+            rules.paddle_spin,
+            rules.ball_acc,
+        ];
+        for float in floats {
+            bytes.extend_from_slice(&float.to_le_bytes());
+        }
+        bytes.push(rules.score_limit);
+        bytes.extend_from_slice(&self.final_score);
+
+        // ticks with the same input of both players are stored once
+        let mut runs: Vec<(u16, [i8; 2])> = Vec::new();
+        for input in &self.inputs {
+            match runs.last_mut() {
+                Some((ticks, last)) if last == input && *ticks < MAX_RUN => *ticks += 1,
+                _ => runs.push((1, *input)),
+            }
+        }
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (ticks, [player_1, player_2]) in runs {
+            bytes.extend_from_slice(&ticks.to_le_bytes());
+            bytes.extend_from_slice(&player_1.to_le_bytes());
+            bytes.extend_from_slice(&player_2.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    // Read a replay from the bytes of a replay file
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Replay> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            bail!("This is not a replay file");
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != VERSION {
+            bail!(
+                "The replay has version {}, but only version {} is supported",
+                version,
+                VERSION
+            );
+        }
+
+        let seed = u64::from_le_bytes(reader.array()?);
+        let mode = match reader.array::<1>()? {
+            [0] => GameMode::Normal,
+            [1] => GameMode::Hard,
+            [mode] => bail!("The replay has the unknown game play mode {}", mode),
+        };
+        let mut float = || -> anyhow::Result<f32> { Ok(f32::from_le_bytes(reader.array()?)) };
+        let field = Vec2::new(float()?, float()?);
+        let paddle_size = Vec2::new(float()?, float()?);
+        let ball_size = Vec2::new(float()?, float()?);
+        let (paddle_speed_normal, paddle_speed_hard) = (float()?, float()?);
+        let (ball_speed_normal, ball_speed_hard) = (float()?, float()?);
+        let (paddle_spin, ball_acc) = (float()?, float()?);
+        let [score_limit] = reader.array()?;
+        let final_score = reader.array()?;
+
+        let rules = Rules {
+            paddle_speed_normal,
+            paddle_speed_hard,
+            ball_speed_normal,
+            ball_speed_hard,
+            paddle_spin,
+            ball_acc,
+            score_limit,
+        };
+        rules.validate().context("The replay has invalid rules")?;
+        let sizes = [
+            ("playfield", field),
+            ("paddles", paddle_size),
+            ("ball", ball_size),
+        ];
+        for (name, size) in sizes {
+            if !size
+                .map(|length| length.is_finite() && length > 0.0)
+                .reduce_and()
+            {
+                bail!("The replay has an invalid size of the {}: {}", name, size);
+            }
+        }
+        // the same minimum as for the window of the game
+        if field.x < MIN_FIELD_WIDTH || field.y < MIN_FIELD_HEIGHT {
+            bail!(
+                "The replay has a playfield of {}x{}, which is smaller than {}x{}",
+                field.x,
+                field.y,
+                MIN_FIELD_WIDTH,
+                MIN_FIELD_HEIGHT
+            );
+        }
+
+        // the runs are checked before they are expanded, a few bytes could fill the memory
+        let run_count = u32::from_le_bytes(reader.array()?);
+        let mut inputs = Vec::new();
+        for _ in 0..run_count {
+            let ticks = u16::from_le_bytes(reader.array()?) as usize;
+            let [player_1, player_2] = reader.array()?;
+            if inputs.len() + ticks > MAX_TICKS {
+                bail!("The replay has more than {} ticks", MAX_TICKS);
+            }
+            inputs.extend(std::iter::repeat_n([player_1 as i8, player_2 as i8], ticks));
+        }
+        if !reader.bytes.is_empty() {
+            bail!("The replay has {} bytes too many", reader.bytes.len());
+        }
+
+        Ok(Replay {
+            seed,
+            mode,
+            rules,
+            field,
+            paddle_size,
+            ball_size,
+            final_score,
+            inputs,
+        })
+    }
+
+    // Load a replay from the given file
+    pub fn load(path: &Path) -> anyhow::Result<Replay> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Failed to read replay file '{}'", path.display()))?;
+        Replay::decode(&bytes)
+            .with_context(|| format!("Failed to load replay file '{}'", path.display()))
+    }
+
+    // Store the replay in a new file within the given directory
+    // - the file name starts with the time of the recording in milliseconds, so that the files
+    // are sorted, a new file never gets the time of an existing or older file, so that it's
+    // neither overwritten nor sorted before the others
+    // - only the newest files will be kept, the oldest files beyond the limit will be deleted
+    // - fails for a replay, which is too long to be loaded again
+    pub fn save_in(&self, directory: &Path, keep: usize) -> anyhow::Result<PathBuf> {
+        if self.tick_count() > MAX_TICKS {
+            bail!("The replay has more than {} ticks", MAX_TICKS);
+        }
+        fs::create_dir_all(directory).with_context(|| {
+            format!(
+                "Failed to create replay directory '{}'",
+                directory.display()
+            )
+        })?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis())
+            .unwrap_or_default();
+        let newest = latest(directory).and_then(|path| recording_time(&path));
+        let mut time = newest.map_or(now, |newest| now.max(newest + 1));
+        let (path, mut file) = loop {
+            let path = directory.join(format!("{:013}-{}.{}", time, self.seed, EXTENSION));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => break (path, file),
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => time += 1,
+                Err(error) => {
+                    return Err(error).with_context(|| {
+                        format!("Failed to create replay file '{}'", path.display())
+                    })
+                }
+            }
+        };
+        file.write_all(&self.encode())
+            .with_context(|| format!("Failed to write replay file '{}'", path.display()))?;
+
+        let files = replay_files(directory)?;
+        for old in files.iter().take(files.len().saturating_sub(keep)) {
+            fs::remove_file(old)
+                .with_context(|| format!("Failed to delete replay file '{}'", old.display()))?;
+        }
+
+        Ok(path)
+    }
+}
+
+// Newest replay file within the given directory
+pub fn latest(directory: &Path) -> Option<PathBuf> {
+    replay_files(directory).ok()?.pop()
+}
+
+// All replay files within the given directory, from the oldest to the newest
+fn replay_files(directory: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Failed to read replay directory '{}'", directory.display()))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == EXTENSION)
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+// Time of the recording in milliseconds, which starts the name of a replay file
+fn recording_time(path: &Path) -> Option<u128> {
+    let name = path.file_stem()?.to_str()?;
+    name.split('-').next()?.parse().ok()
+}
+
+// Round the movement of a paddle to the recorded steps
+fn quantize(intent: PaddleIntent) -> i8 {
+    (intent.axis.clamp(-1.0, 1.0) * INTENT_STEPS).round() as i8
+}
+
+fn dequantize(input: i8) -> PaddleIntent {
+    PaddleIntent {
+        axis: input as f32 / INTENT_STEPS,
+    }
+}
+
+/// Bytes of a replay file, which have not been read yet
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    // Read the next bytes
+    fn take(&mut self, count: usize) -> anyhow::Result<&'a [u8]> {
+        if self.bytes.len() < count {
+            bail!("The replay ends too early");
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+/// Plays back the recorded input of one player, tick by tick
+pub struct PlaybackController {
+    intents: Vec<PaddleIntent>,
+    tick: usize,
+}
+
+impl PaddleController for PlaybackController {
+    // The paddle stands still after the end of the recording
+    fn intent(&mut self, _simulation: &Simulation, _input: &DeviceInput) -> PaddleIntent {
+        let intent = self.intents.get(self.tick).copied().unwrap_or_default();
+        self.tick += 1;
+        intent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Difficulty, Opponent};
+    use crate::util::{BALL_SIZE, PADDLE_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+    /// Longest recorded match in ticks
+    const MAX_MATCH_TICKS: usize = 100_000;
+
+    #[test]
+    fn resimulated_match_ends_like_the_recorded_one() {
+        let mut simulation = Simulation::new(
+            Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+            PADDLE_SIZE,
+            BALL_SIZE,
+            Rules {
+                score_limit: 3,
+                ..Rules::default()
+            },
+        );
+        simulation.start(GameMode::Hard, 42);
+        let mut opponents = [
+            Opponent::new(Player::One, Difficulty::Normal, false, 1),
+            Opponent::new(Player::Two, Difficulty::Hard, true, 2),
+        ];
+
+        // record a match between two computer opponents like the game does
+        let mut recording = Replay::record(&simulation, GameMode::Hard);
+        for _ in 0..MAX_MATCH_TICKS {
+            let intents = opponents
+                .each_mut()
+                .map(|opponent| opponent.intent(&simulation));
+            let [player_1, player_2] = recording.push(intents);
+            simulation.step(SIMULATION_TIMESTEP, player_1, player_2);
+            if simulation.winner().is_some() {
+                break;
+            }
+        }
+        recording.finish(&simulation);
+        assert!(simulation.winner().is_some());
+
+        let replay = Replay::decode(&recording.encode()).unwrap();
+        assert_eq!(replay, recording);
+
+        let resimulated = replay.resimulate();
+        assert_eq!(
+            [
+                resimulated.score(Player::One),
+                resimulated.score(Player::Two)
+            ],
+            replay.final_score
+        );
+        assert_eq!(resimulated.ball, simulation.ball);
+        assert_eq!(resimulated.player_1, simulation.player_1);
+        assert_eq!(resimulated.player_2, simulation.player_2);
+    }
+
+    // Replay of a match without any ticks
+    fn empty_replay(rules: Rules) -> Replay {
+        let simulation = Simulation::new(
+            Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+            PADDLE_SIZE,
+            BALL_SIZE,
+            rules,
+        );
+        Replay::record(&simulation, GameMode::Normal)
+    }
+
+    #[test]
+    fn decode_rejects_too_many_ticks() {
+        // replace the runs of the empty replay by runs with too many ticks in total
+        let mut bytes = empty_replay(Rules::default()).encode();
+        bytes.truncate(bytes.len() - 4);
+        let runs = MAX_TICKS / MAX_RUN as usize + 1;
+        bytes.extend_from_slice(&(runs as u32).to_le_bytes());
+        for _ in 0..runs {
+            bytes.extend_from_slice(&MAX_RUN.to_le_bytes());
+            bytes.extend_from_slice(&[0, 0]);
+        }
+
+        assert!(Replay::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn decode_rejects_invalid_rules() {
+        let invalid = [
+            Rules {
+                ball_speed_normal: f32::NAN,
+                ..Rules::default()
+            },
+            Rules {
+                paddle_speed_hard: -1.0,
+                ..Rules::default()
+            },
+            Rules {
+                score_limit: 0,
+                ..Rules::default()
+            },
+        ];
+        for rules in invalid {
+            assert!(Replay::decode(&empty_replay(rules).encode()).is_err());
+        }
+        assert!(Replay::decode(&empty_replay(Rules::default()).encode()).is_ok());
+    }
+
+    #[test]
+    fn decode_rejects_playfield_below_minimum() {
+        let mut replay = empty_replay(Rules::default());
+        for field in [
+            Vec2::new(MIN_FIELD_WIDTH - 1.0, MIN_FIELD_HEIGHT),
+            Vec2::new(MIN_FIELD_WIDTH, MIN_FIELD_HEIGHT - 1.0),
+        ] {
+            replay.field = field;
+            assert!(Replay::decode(&replay.encode()).is_err());
+        }
+
+        replay.field = Vec2::new(MIN_FIELD_WIDTH, MIN_FIELD_HEIGHT);
+        assert!(Replay::decode(&replay.encode()).is_ok());
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::Context as anyhow_context;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tetra::graphics::text::Font;
+use tetra::graphics::Texture;
+use tetra::Context;
+
+/// Environment variable to override the directory of the resources
+pub const RESOURCES_ENV: &str = "PONG_GAME_RESOURCES";
+
+/// Name of the sub directory within the data directories of the platform
+const DATA_DIR_NAME: &str = "pong-game";
+
+/// Name of the directory, which holds the resources
+const RESOURCES_DIR_NAME: &str = "resources";
+
+/// All resources compiled into the binary, when the feature 'embed-resources' is enabled
+#[cfg(feature = "embed-resources")]
+const EMBEDDED: &[(&str, &[u8])] = &[
+    ("ball.png", include_bytes!("../resources/ball.png")),
+    ("player1.png", include_bytes!("../resources/player1.png")),
+    ("player2.png", include_bytes!("../resources/player2.png")),
+    ("pong.ttf", include_bytes!("../resources/pong.ttf")),
+    ("comic.ttf", include_bytes!("../resources/comic.ttf")),
+    ("lang/de.toml", include_bytes!("../resources/lang/de.toml")),
+    ("lang/fr.toml", include_bytes!("../resources/lang/fr.toml")),
+];
+
+#[cfg(not(feature = "embed-resources"))]
+const EMBEDDED: &[(&str, &[u8])] = &[];
+
+/// Where a resource was found
+enum Source {
+    File(PathBuf),
+    Embedded(&'static [u8]),
+}
+
+/// Finds the resources of the game independent of the current working directory.
+/// A resource will be searched in this order:
+/// 1. the directory given by the environment variable 'PONG_GAME_RESOURCES'
+/// 2. the 'resources' directory next to the executable and the directory of the executable itself
+/// 3. the 'pong-game/resources' directories within the data directories of the platform,
+///    e.g. '~/.local/share' and the XDG_DATA_DIRS on Linux
+/// 4. the 'resources' directory within the current working directory, e.g. for 'cargo run'
+/// 5. the resources compiled into the binary, if the feature 'embed-resources' is enabled
+pub struct ResourceLocator {
+    search_dirs: Vec<PathBuf>,
+}
+
+impl ResourceLocator {
+    // constructor which collects all directories to search in
+    pub fn new() -> Self {
+        let mut search_dirs = Vec::new();
+
+        if let Some(dir) = env::var_os(RESOURCES_ENV) {
+            search_dirs.push(PathBuf::from(dir));
+        }
+
+        if let Some(exe_dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            search_dirs.push(exe_dir.join(RESOURCES_DIR_NAME));
+            search_dirs.push(exe_dir);
+        }
+
+        for data_dir in data_dirs() {
+            search_dirs.push(data_dir.join(DATA_DIR_NAME).join(RESOURCES_DIR_NAME));
+        }
+
+        search_dirs.push(PathBuf::from(RESOURCES_DIR_NAME));
+
+        Self { search_dirs }
+    }
+
+    // Load a texture
+    // - name is the file name of the resource or an absolute path
+    pub fn texture(&self, ctx: &mut Context, name: &str) -> anyhow::Result<Texture> {
+        let texture = match self.find(name)? {
+            Source::File(path) => Texture::new(ctx, &path)
+                .with_context(|| format!("Failed to load image '{}'", path.display()))?,
+            Source::Embedded(data) => Texture::from_encoded(ctx, data)
+                .with_context(|| format!("Failed to load embedded image '{}'", name))?,
+        };
+
+        Ok(texture)
+    }
+
+    // Load a vector font with the given size
+    // - name is the file name of the resource or an absolute path
+    pub fn font(&self, ctx: &mut Context, name: &str, size: f32) -> anyhow::Result<Font> {
+        let font = match self.find(name)? {
+            Source::File(path) => Font::vector(ctx, &path, size)
+                .with_context(|| format!("Failed to load font '{}'", path.display()))?,
+            Source::Embedded(data) => Font::from_vector_file_data(ctx, data, size)
+                .with_context(|| format!("Failed to load embedded font '{}'", name))?,
+        };
+
+        Ok(font)
+    }
+
+    // Load a text file, e.g. a string table
+    // - name is the file name of the resource or an absolute path
+    pub fn text(&self, name: &str) -> anyhow::Result<String> {
+        let text = match self.find(name)? {
+            Source::File(path) => fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file '{}'", path.display()))?,
+            Source::Embedded(data) => String::from_utf8(data.to_vec())
+                .with_context(|| format!("Embedded file '{}' is no valid UTF-8", name))?,
+        };
+
+        Ok(text)
+    }
+
+    // Search the resource in all directories, before falling back to the embedded resources
+    fn find(&self, name: &str) -> anyhow::Result<Source> {
+        let path = Path::new(name);
+        if path.is_absolute() {
+            return Ok(Source::File(path.to_path_buf()));
+        }
+
+        if let Some(file) = self
+            .search_dirs
+            .iter()
+            .map(|dir| dir.join(path))
+            .find(|file| file.is_file())
+        {
+            return Ok(Source::File(file));
+        }
+
+        if let Some((_, data)) = EMBEDDED.iter().find(|(embedded, _)| *embedded == name) {
+            return Ok(Source::Embedded(data));
+        }
+
+        let searched: Vec<String> = self
+            .search_dirs
+            .iter()
+            .map(|dir| format!("'{}'", dir.display()))
+            .collect();
+
+        anyhow::bail!(
+            "Resource '{}' not found, searched in {} - set the environment variable {} to the directory of the resources",
+            name,
+            searched.join(", "),
+            RESOURCES_ENV
+        )
+    }
+}
+
+// Data directories of the platform, the user specific one comes first
+fn data_dirs() -> Vec<PathBuf> {
+    let mut data_dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+
+    // system wide directories according to the XDG base directory specification
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        let system_dirs = env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_owned());
+
+        data_dirs.extend(
+            system_dirs
+                .split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+
+    data_dirs
+}
@@ -0,0 +1,203 @@
+use super::settings::CONTROLS_ENTRY;
+use super::{Scene, Settings, Transition};
+use crate::action::{Action, Scope};
+use crate::controls::key_label;
+use crate::game::{GameData, TableLine, TextEntity, TextTable};
+use tetra::graphics::text::Text;
+use tetra::input::Key;
+use tetra::math::Vec2;
+use tetra::{Context, Event};
+
+/// Number of entries: all actions and the reset to the default keys
+const ENTRY_COUNT: usize = Action::ALL.len() + 1;
+
+/// Screen to bind a new key to each action.
+/// After choosing an action, the next key press will be bound to it,
+/// unless the key is already used by another action at the same time.
+pub struct KeyBindings {
+    header: TextEntity,
+    entries: TextTable,
+    status: TextEntity,
+    selected: usize,
+    /// Action which waits for its new key
+    capturing: Option<Action>,
+}
+
+impl KeyBindings {
+    // constructor for the key bindings screen
+    pub fn new(ctx: &mut Context, data: &GameData) -> Self {
+        let mut key_bindings = Self {
+            header: TextEntity::anchored(
+                ctx,
+                Text::new(
+                    data.strings.get("controls_title"),
+                    data.assets.menu_header_font.clone(),
+                ),
+                data.simulation.field(),
+                Vec2::new(0.5, 0.06),
+            ),
+            entries: Self::entries(ctx, data, 0, None),
+            status: Self::status(ctx, data, String::new()),
+            selected: 0,
+            capturing: None,
+        };
+        key_bindings.show_usage(ctx, data);
+
+        key_bindings
+    }
+
+    // Table with the key of each action, the selected key is enclosed in arrows
+    // and will be replaced by a question mark while waiting for the new key
+    fn entries(
+        ctx: &mut Context,
+        data: &GameData,
+        selected: usize,
+        capturing: Option<Action>,
+    ) -> TextTable {
+        let strings = &data.strings;
+        let mark = |index: usize, value: String| match index == selected {
+            true => format!("< {} >", value),
+            false => value,
+        };
+
+        let mut lines: Vec<TableLine> = Action::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, action)| {
+                let key = match capturing {
+                    Some(capturing) if capturing == *action => "?".to_owned(),
+                    _ => data.controls.key_name(ctx, *action),
+                };
+                TableLine::Row(strings.get(action.text_key()).to_owned(), mark(index, key))
+            })
+            .collect();
+        lines.push(TableLine::Row(
+            strings.get("controls_reset").to_owned(),
+            mark(Action::ALL.len(), "...".to_owned()),
+        ));
+
+        TextTable::anchored(
+            ctx,
+            &lines,
+            &data.assets.menu_usage_font,
+            data.simulation.field(),
+            Vec2::new(0.5, 0.5),
+        )
+    }
+
+    // Single line at the bottom of the screen
+    fn status(ctx: &mut Context, data: &GameData, message: String) -> TextEntity {
+        TextEntity::anchored(
+            ctx,
+            Text::new(message, data.assets.menu_usage_font.clone()),
+            data.simulation.field(),
+            Vec2::new(0.5, 0.95),
+        )
+    }
+
+    // Show the keys to use this screen in the status line
+    fn show_usage(&mut self, ctx: &mut Context, data: &GameData) {
+        let message = format!(
+            "{}: {}    {}: {}",
+            data.controls.key_name(ctx, Action::Confirm),
+            data.strings.get("controls_change"),
+            data.controls.key_name(ctx, Action::Back),
+            data.strings.get("action_back"),
+        );
+        self.status = Self::status(ctx, data, message);
+    }
+
+    // Bind the pressed key to the action, the key of 'Back' cancels the binding
+    fn capture(&mut self, ctx: &mut Context, data: &mut GameData, action: Action, key: Key) {
+        self.capturing = None;
+
+        if action != Action::Back && key == data.controls.key(Action::Back) {
+            self.show_usage(ctx, data);
+            return;
+        }
+
+        match data.change_binding(action, key) {
+            Ok(()) => self.show_usage(ctx, data),
+            Err(other) => {
+                let message = data
+                    .strings
+                    .get("controls_conflict")
+                    .replace("{key}", &key_label(ctx, key))
+                    .replace("{action}", data.strings.get(other.text_key()));
+                self.status = Self::status(ctx, data, message);
+            }
+        }
+    }
+}
+
+impl Scene for KeyBindings {
+    fn draw(&mut self, ctx: &mut Context, _data: &mut GameData) -> anyhow::Result<()> {
+        self.header.draw(ctx);
+        self.entries.draw(ctx);
+        self.status.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        if let Some(action) = self.capturing {
+            let Event::KeyPressed { key } = event else {
+                return Ok(Transition::None);
+            };
+            self.capture(ctx, data, action, key);
+        } else {
+            // a click selects and changes the clicked entry
+            let action = match data.clicked(ctx, &event) {
+                Some(position) => self.entries.line_at(position).map(|line| {
+                    self.selected = line;
+                    Action::Confirm
+                }),
+                None => data.controls.pressed(&event, Scope::Menu),
+            };
+
+            match action {
+                Some(Action::MenuUp) => {
+                    self.selected = (self.selected + ENTRY_COUNT - 1) % ENTRY_COUNT;
+                }
+                Some(Action::MenuDown) => {
+                    self.selected = (self.selected + 1) % ENTRY_COUNT;
+                }
+                Some(Action::Confirm) if self.selected == Action::ALL.len() => {
+                    data.reset_bindings()?;
+                    self.show_usage(ctx, data);
+                }
+                Some(Action::Confirm) => {
+                    let action = Action::ALL[self.selected];
+                    let message = data
+                        .strings
+                        .get("controls_press_key")
+                        .replace("{key}", &data.controls.key_name(ctx, Action::Back));
+                    self.status = Self::status(ctx, data, message);
+                    self.capturing = Some(action);
+                }
+                Some(Action::Back) => {
+                    // the settings will be rebuilt, to show the new keys
+                    return Ok(Transition::Replace(Box::new(Settings::new(
+                        ctx,
+                        data,
+                        CONTROLS_ENTRY,
+                    ))));
+                }
+                _ => return Ok(Transition::None),
+            }
+        }
+
+        self.entries = Self::entries(ctx, data, self.selected, self.capturing);
+
+        Ok(Transition::None)
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.capturing.is_some()
+    }
+}
@@ -0,0 +1,233 @@
+use super::{Playing, ReplayViewer, Scene, Settings, Transition};
+use crate::action::{Action, Scope};
+use crate::ai::{Difficulty, Opponent};
+use crate::config::Controller;
+use crate::game::{GameData, TableLine, TextEntity, TextTable};
+use crate::replay::{self, Replay};
+use crate::simulation::{GameMode, Player};
+use crate::util::SIMULATION_TIMESTEP;
+use tetra::graphics::text::Text;
+use tetra::math::Vec2;
+use tetra::{window, Context, Event};
+
+/// Opacity of the shade between the demo match and the texts of the menu
+const DEMO_SHADE_OPACITY: f32 = 0.6;
+
+/// Main menu, which is shown on game start or if the player went back from a match.
+/// Behind the menu two computer opponents play a demo match, if the demo is enabled.
+pub struct MainMenu {
+    header: TextEntity,
+    usage: TextTable,
+    /// Action of each line of the usage, which will be triggered by clicking the line
+    buttons: Vec<Option<Action>>,
+    /// Computer opponents of the demo match, they learn nothing about the human players
+    demo: Option<[Opponent; 2]>,
+}
+
+impl MainMenu {
+    // constructor for the main menu
+    pub fn new(ctx: &mut Context, data: &GameData) -> Self {
+        let field = data.simulation.field();
+        let strings = &data.strings;
+        let controls = &data.controls;
+        let row = |keys: String, text: &str| TableLine::Row(keys, strings.get(text).to_owned());
+        let key = |action: Action| controls.key_name(ctx, action);
+        let keys = |up: Action, down: Action| format!("{} / {}", key(up), key(down));
+        let heading = |text: &str| (TableLine::Text(strings.get(text).to_owned()), None);
+        let button = |action: Action, text: &str| (row(key(action), text), Some(action));
+        let info = |line: TableLine| (line, None);
+        let controller = |action: Action, player: Player| {
+            let name = match data.config.players.controller(player) {
+                Controller::Human => "controller_human",
+                Controller::Keyboard => "controller_keyboard",
+                Controller::Gamepad => "controller_gamepad",
+                Controller::Mouse => "controller_mouse",
+                Controller::CpuEasy => "controller_cpu_easy",
+                Controller::CpuNormal => "controller_cpu_normal",
+                Controller::CpuHard => "controller_cpu_hard",
+                Controller::CpuAdaptive => "controller_cpu_adaptive",
+                Controller::CpuPerfect => "controller_cpu_perfect",
+                Controller::External => "controller_external",
+            };
+            let text = strings
+                .get("menu_controller")
+                .replace("{controller}", strings.get(name));
+            (TableLine::Row(key(action), text), Some(action))
+        };
+
+        let (usage, buttons): (Vec<TableLine>, Vec<Option<Action>>) = [
+            button(Action::Quit, "menu_quit"),
+            info(row(key(Action::Pause), "menu_pause")),
+            info(row(key(Action::Back), "menu_main_menu")),
+            button(Action::Settings, "menu_settings"),
+            button(Action::SwitchDisplayMode, "menu_display_mode"),
+            button(Action::WatchReplay, "menu_watch_replay"),
+            info(TableLine::Empty),
+            heading("menu_player_1"),
+            controller(Action::SwitchPlayer1, Player::One),
+            info(row(
+                keys(Action::Player1Up, Action::Player1Down),
+                "menu_move_paddle",
+            )),
+            info(TableLine::Empty),
+            heading("menu_player_2"),
+            controller(Action::SwitchPlayer2, Player::Two),
+            info(row(
+                keys(Action::Player2Up, Action::Player2Down),
+                "menu_move_paddle",
+            )),
+            info(TableLine::Empty),
+            heading("menu_choose_mode"),
+            button(Action::StartNormal, "mode_normal"),
+            button(Action::StartHard, "mode_hard"),
+        ]
+        .into_iter()
+        .unzip();
+
+        Self {
+            header: TextEntity::anchored(
+                ctx,
+                Text::new(
+                    strings.get("menu_title"),
+                    data.assets.menu_header_font.clone(),
+                ),
+                field,
+                Vec2::new(0.5, 0.1),
+            ),
+            usage: TextTable::anchored(
+                ctx,
+                &usage,
+                &data.assets.menu_usage_font,
+                field,
+                Vec2::new(0.5, 0.55),
+            ),
+            buttons,
+            demo: None,
+        }
+    }
+
+    // Start a new demo match with a random seed
+    fn start_demo(&mut self, data: &mut GameData) {
+        let seed: u64 = rand::random();
+        data.simulation.start(GameMode::Normal, seed);
+        data.update_score_text(Player::One);
+        data.update_score_text(Player::Two);
+
+        let opponent = |player: Player, offset: u64| {
+            Opponent::new(player, Difficulty::Hard, false, seed.wrapping_add(offset))
+        };
+        self.demo = Some([opponent(Player::One, 1), opponent(Player::Two, 2)]);
+    }
+}
+
+impl Scene for MainMenu {
+    fn update(&mut self, _ctx: &mut Context, data: &mut GameData) -> anyhow::Result<Transition> {
+        if !data.config.demo.enabled {
+            return Ok(Transition::None);
+        }
+
+        // the demo starts over after the end of each demo match and after a real match
+        if self.demo.is_none() || !data.simulation.is_running() {
+            self.start_demo(data);
+        }
+
+        if let Some([opponent_1, opponent_2]) = &mut self.demo {
+            let intent_player_1 = opponent_1.intent(&data.simulation);
+            let intent_player_2 = opponent_2.intent(&data.simulation);
+            if let Some(player) =
+                data.simulation
+                    .step(SIMULATION_TIMESTEP, intent_player_1, intent_player_2)
+            {
+                data.update_score_text(player);
+            }
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<()> {
+        // the demo stays in the background, so that the menu is readable
+        if self.demo.is_some() {
+            data.draw_playfield(ctx);
+            data.draw_match_objects(ctx);
+            data.playfield.draw_shade(ctx, DEMO_SHADE_OPACITY);
+        }
+
+        self.header.draw(ctx);
+        self.usage.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        // a click triggers the action of the clicked line, the global actions of the keys
+        // have already been handled for all scenes
+        let action = match data.clicked(ctx, &event) {
+            Some(position) => {
+                match self
+                    .usage
+                    .line_at(position)
+                    .and_then(|line| self.buttons[line])
+                {
+                    Some(Action::Quit) => {
+                        window::quit(ctx);
+                        None
+                    }
+                    Some(Action::SwitchDisplayMode) => {
+                        data.switch_display_mode(ctx);
+                        None
+                    }
+                    action => action,
+                }
+            }
+            None => data.controls.pressed(&event, Scope::Menu),
+        };
+
+        // start playing with the chosen game play mode
+        let mode = match action {
+            Some(Action::StartNormal) => GameMode::Normal,
+            Some(Action::StartHard) => GameMode::Hard,
+            Some(Action::Settings) => {
+                return Ok(Transition::Push(Box::new(Settings::new(ctx, data, 0))));
+            }
+            Some(Action::WatchReplay) => {
+                // the newest replay, nothing happens before the first recorded match
+                let Some(path) = replay::latest(&data.replay_directory()) else {
+                    return Ok(Transition::None);
+                };
+                let viewer =
+                    Replay::load(&path).and_then(|replay| ReplayViewer::new(ctx, data, &replay));
+                return match viewer {
+                    Ok(viewer) => Ok(Transition::Push(Box::new(viewer))),
+                    Err(err) => {
+                        eprintln!("{:#}", err);
+                        Ok(Transition::None)
+                    }
+                };
+            }
+            Some(action @ (Action::SwitchPlayer1 | Action::SwitchPlayer2)) => {
+                // the menu will be rebuilt to show the new controller
+                let mut players = data.config.players;
+                match action {
+                    Action::SwitchPlayer1 => players.player_1 = players.player_1.next(),
+                    _ => players.player_2 = players.player_2.next(),
+                }
+                data.change_players(players);
+                let demo = self.demo.take();
+                *self = MainMenu::new(ctx, data);
+                self.demo = demo;
+                return Ok(Transition::None);
+            }
+            _ => return Ok(Transition::None),
+        };
+
+        data.start_match(mode);
+
+        Ok(Transition::Push(Box::new(Playing::new())))
+    }
+}
@@ -0,0 +1,80 @@
+use super::{Scene, Transition};
+use crate::action::{Action, Scope};
+use crate::game::{GameData, TableLine, TextEntity, TextTable};
+use crate::simulation::Player;
+use tetra::graphics::text::Text;
+use tetra::math::Vec2;
+use tetra::{Context, Event};
+
+/// Screen which shows the final score and the winner of the match
+pub struct MatchOver {
+    winner_msg: TextEntity,
+    usage: TextTable,
+}
+
+impl MatchOver {
+    // constructor for the match over screen
+    pub fn new(ctx: &mut Context, data: &GameData, winner: Player) -> Self {
+        // draw the winner message on the side of the winner
+        let x = match winner {
+            Player::One => 0.25,
+            Player::Two => 0.75,
+        };
+        let field = data.simulation.field();
+
+        Self {
+            winner_msg: TextEntity::anchored(
+                ctx,
+                Text::new(
+                    data.strings.get("win_title"),
+                    data.assets.menu_header_font.clone(),
+                ),
+                field,
+                Vec2::new(x, 0.3),
+            ),
+            usage: TextTable::anchored(
+                ctx,
+                &[TableLine::Row(
+                    data.controls.key_name(ctx, Action::Back),
+                    data.strings.get("menu_main_menu").to_owned(),
+                )],
+                &data.assets.menu_header_font,
+                field,
+                Vec2::new(x, 0.4),
+            ),
+        }
+    }
+}
+
+impl Scene for MatchOver {
+    fn draw(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<()> {
+        // draw the endpoint status
+        data.draw_playfield(ctx);
+        self.winner_msg.draw(ctx);
+        self.usage.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        // the only line of the usage goes back to the main menu
+        let action = match data.clicked(ctx, &event) {
+            Some(position) => self.usage.line_at(position).map(|_| Action::Back),
+            None => data.controls.pressed(&event, Scope::Match),
+        };
+
+        match action {
+            Some(Action::Back) => {
+                // back to the main menu
+                data.reset_match();
+                Ok(Transition::Pop)
+            }
+            _ => Ok(Transition::None),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::game::GameData;
+use tetra::{Context, Event};
+
+mod key_bindings;
+mod main_menu;
+mod match_over;
+mod paused;
+mod playing;
+mod replay_viewer;
+mod settings;
+
+pub use key_bindings::KeyBindings;
+pub use main_menu::MainMenu;
+pub use match_over::MatchOver;
+pub use paused::Paused;
+pub use playing::Playing;
+pub use replay_viewer::ReplayViewer;
+pub use settings::Settings;
+
+/// Change of the scene stack, requested by the scene on top of the stack
+pub enum Transition {
+    /// Keep the current scene
+    None,
+    /// Put a new scene on top of the current one, e.g. an overlay like the pause screen
+    Push(Box<dyn Scene>),
+    /// Remove the current scene and go back to the one below
+    Pop,
+    /// Exchange the current scene with a new one
+    Replace(Box<dyn Scene>),
+    /// Remove all scenes and start again with the given one
+    Reset(Box<dyn Scene>),
+}
+
+/// A single screen of the game, like the main menu or the running match.
+/// Only the scene on top of the stack receives updates and events,
+/// while all visible scenes will be drawn from the bottom to the top.
+pub trait Scene {
+    // Called for every tick of the game loop
+    fn update(&mut self, _ctx: &mut Context, _data: &mut GameData) -> anyhow::Result<Transition> {
+        Ok(Transition::None)
+    }
+
+    // Called for every frame
+    fn draw(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<()>;
+
+    // Called when a window or input event occurs
+    fn event(
+        &mut self,
+        _ctx: &mut Context,
+        _data: &mut GameData,
+        _event: Event,
+    ) -> anyhow::Result<Transition> {
+        Ok(Transition::None)
+    }
+
+    // While a scene captures the keys, it receives every key press - even the keys of the
+    // actions, which are usually handled for all scenes, like quitting the game
+    fn captures_keys(&self) -> bool {
+        false
+    }
+
+    // While a scene captures the mouse, the mouse moves a paddle instead of the cursor,
+    // if the player chose a captured mouse in the settings
+    fn captures_mouse(&self) -> bool {
+        false
+    }
+
+    // An overlay will be drawn on top of the scene below, instead of hiding it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
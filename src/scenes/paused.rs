@@ -0,0 +1,96 @@
+use super::{MainMenu, Scene, Transition};
+use crate::action::{Action, Scope};
+use crate::game::{GameData, TableLine, TextEntity, TextTable};
+use tetra::graphics::text::Text;
+use tetra::math::Vec2;
+use tetra::{Context, Event};
+
+/// Opacity of the shade, which dims the frozen match
+const SHADE_OPACITY: f32 = 0.5;
+
+/// Pause screen, which will be shown on top of the running match.
+/// Only the match is frozen, because the playing scene does not receive any updates
+/// while the pause screen is on top of it - the game loop itself keeps running.
+pub struct Paused {
+    title: TextEntity,
+    usage: TextTable,
+}
+
+impl Paused {
+    // constructor for the pause screen
+    pub fn new(ctx: &mut Context, data: &GameData) -> Self {
+        // center the pause message on screen
+        let field = data.simulation.field();
+        let strings = &data.strings;
+        let title = TextEntity::anchored(
+            ctx,
+            Text::new(
+                strings.get("pause_title"),
+                data.assets.menu_header_font.clone(),
+            ),
+            field,
+            Vec2::new(0.5, 0.42),
+        );
+        let usage = TextTable::anchored(
+            ctx,
+            &[
+                TableLine::Row(
+                    data.controls.key_name(ctx, Action::Pause),
+                    strings.get("pause_resume").to_owned(),
+                ),
+                TableLine::Row(
+                    data.controls.key_name(ctx, Action::Back),
+                    strings.get("menu_main_menu").to_owned(),
+                ),
+            ],
+            &data.assets.menu_header_font,
+            field,
+            Vec2::new(0.5, 0.55),
+        );
+
+        Self { title, usage }
+    }
+}
+
+impl Scene for Paused {
+    fn draw(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<()> {
+        // translucent layer to dim the frozen match
+        data.playfield.draw_shade(ctx, SHADE_OPACITY);
+        self.title.draw(ctx);
+        self.usage.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        // the lines of the usage resume the match or go back to the main menu
+        let action = match data.clicked(ctx, &event) {
+            Some(position) => match self.usage.line_at(position) {
+                Some(0) => Some(Action::Pause),
+                Some(_) => Some(Action::Back),
+                None => None,
+            },
+            None => data.controls.pressed(&event, Scope::Match),
+        };
+
+        match action {
+            // resume the match
+            Some(Action::Pause) => Ok(Transition::Pop),
+            Some(Action::Back) => {
+                // leave the match and go back to the main menu
+                data.reset_match();
+                Ok(Transition::Reset(Box::new(MainMenu::new(ctx, data))))
+            }
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
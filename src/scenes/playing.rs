@@ -0,0 +1,144 @@
+use super::MainMenu;
+use super::{MatchOver, Paused, Scene, Transition};
+use crate::action::{Action, Scope};
+use crate::config::{Controller, GamepadSlot};
+use crate::game::GameData;
+use crate::simulation::{PaddleIntent, Player};
+use crate::util::{DEFAULT_UPDATE_RATE, SIMULATION_TIMESTEP};
+use tetra::input;
+use tetra::{Context, Event};
+
+/// The running match
+pub struct Playing {
+    /// Ticks since the last input of a player
+    idle_ticks: u32,
+}
+
+impl Playing {
+    pub fn new() -> Self {
+        Self { idle_ticks: 0 }
+    }
+}
+
+impl Scene for Playing {
+    fn update(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<Transition> {
+        // each controller moves its paddle, the human players by the keyboard, the gamepad
+        // or the mouse
+        let input = data.device_input(ctx);
+        let simulation = &data.simulation;
+        let [intent_player_1, intent_player_2] =
+            data.controllers
+                .each_mut()
+                .map(|controller| match controller {
+                    Some(controller) => controller.intent(simulation, &input),
+                    None => PaddleIntent::default(),
+                });
+
+        // an abandoned match goes back to the demo in the main menu
+        // - only the devices count, because a mouse moves its paddle towards the resting cursor
+        // - a match without human players is never abandoned
+        let human = data
+            .controllers
+            .iter()
+            .flatten()
+            .any(|controller| controller.is_human());
+        if !human || input.is_active() || input::get_keys_down(ctx).next().is_some() {
+            self.idle_ticks = 0;
+        } else {
+            self.idle_ticks += 1;
+        }
+        let idle_timeout = data.config.demo.idle_timeout;
+        if idle_timeout > 0 && self.idle_ticks as f64 >= idle_timeout as f64 * DEFAULT_UPDATE_RATE {
+            data.reset_match();
+            return Ok(Transition::Reset(Box::new(MainMenu::new(ctx, data))));
+        }
+
+        // the recording rounds the input, so that its replay uses exactly the same input
+        let [intent_player_1, intent_player_2] = match &mut data.recording {
+            Some(recording) => recording.push([intent_player_1, intent_player_2]),
+            None => [intent_player_1, intent_player_2],
+        };
+
+        // advance the match by one fixed tick and update the score, if one player scored
+        if let Some(player) =
+            data.simulation
+                .step(SIMULATION_TIMESTEP, intent_player_1, intent_player_2)
+        {
+            data.update_score_text(player);
+        }
+
+        // check score limit -> if it's reached -> game ends
+        match data.simulation.winner() {
+            Some(winner) => {
+                data.save_replay();
+                Ok(Transition::Replace(Box::new(MatchOver::new(
+                    ctx, data, winner,
+                ))))
+            }
+            None => Ok(Transition::None),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<()> {
+        data.draw_playfield(ctx);
+        data.draw_match_objects(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        // every key, button or mouse movement is an input
+        if matches!(
+            event,
+            Event::KeyPressed { .. }
+                | Event::GamepadButtonPressed { .. }
+                | Event::MouseButtonPressed { .. }
+                | Event::MouseMoved { .. }
+        ) {
+            self.idle_ticks = 0;
+        }
+
+        // a human player lost the gamepad -> pause, until it will be connected again
+        let gamepads = &data.config.gamepads;
+        let gamepad_lost = matches!(event, Event::GamepadRemoved { .. })
+            && [
+                (Player::One, gamepads.player_1),
+                (Player::Two, gamepads.player_2),
+            ]
+            .into_iter()
+            .any(|(player, slot)| {
+                matches!(
+                    data.config.players.controller(player),
+                    Controller::Human | Controller::Gamepad
+                ) && slot != GamepadSlot::None
+                    && !data.gamepads.is_connected(slot)
+            });
+
+        match data.controls.pressed(&event, Scope::Match) {
+            _ if gamepad_lost => {
+                data.simulation.settle();
+                Ok(Transition::Push(Box::new(Paused::new(ctx, data))))
+            }
+            Some(Action::Pause) => {
+                // freeze the match by putting the pause screen on top of it
+                data.simulation.settle();
+                Ok(Transition::Push(Box::new(Paused::new(ctx, data))))
+            }
+            Some(Action::Back) => {
+                // back to the main menu
+                data.reset_match();
+                Ok(Transition::Pop)
+            }
+            _ => Ok(Transition::None),
+        }
+    }
+
+    fn captures_mouse(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,237 @@
+use super::{MainMenu, Scene, Transition};
+use crate::action::{Action, Scope};
+use crate::controller::{DeviceInput, PaddleController};
+use crate::game::{GameData, TableLine, TextEntity, TextTable};
+use crate::replay::{PlaybackController, Replay};
+use crate::simulation::{Player, Simulation};
+use crate::util::SIMULATION_TIMESTEP;
+use tetra::graphics::text::Text;
+use tetra::math::Vec2;
+use tetra::{Context, Event};
+
+/// Speeds of the playback, relative to the speed of the match
+const SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Index of the speed of the match within the speeds of the playback
+const NORMAL_SPEED: usize = 2;
+
+/// Opacity of the shade, which dims the paused replay
+const SHADE_OPACITY: f32 = 0.5;
+
+/// Plays back a recorded match tick by tick, which can be paused, slowed down, sped up
+/// and advanced by single ticks while it's paused.
+/// The replay runs in its own simulation with the recorded rules and the recorded size of the
+/// playfield, which replace the simulation and the playfield of the game until the player goes
+/// back to the main menu.
+pub struct ReplayViewer {
+    playback: [PlaybackController; 2],
+    /// Simulation of the game, which will be restored after the replay
+    live: Option<Simulation>,
+    tick: usize,
+    tick_count: usize,
+    paused: bool,
+    /// Index of the current speed
+    speed: usize,
+    /// Part of a tick, which is due at a slow speed
+    pending: f32,
+    title: TextEntity,
+    status: TextEntity,
+    usage: TextTable,
+}
+
+impl ReplayViewer {
+    // constructor for the replay viewer
+    pub fn new(ctx: &mut Context, data: &mut GameData, replay: &Replay) -> anyhow::Result<Self> {
+        // a replay, which does not fit on the screen, leaves the game as it was
+        let live = std::mem::replace(&mut data.simulation, replay.simulation());
+        if let Err(err) = data.fit_field(ctx) {
+            data.simulation = live;
+            data.fit_field(ctx)?;
+            return Err(err.context("Failed to show the playfield of the replay"));
+        }
+        data.update_score_text(Player::One);
+        data.update_score_text(Player::Two);
+
+        let field = data.simulation.field();
+        let strings = &data.strings;
+        let controls = &data.controls;
+        let row = |keys: String, text: &str| TableLine::Row(keys, strings.get(text).to_owned());
+        let key = |action: Action| controls.key_name(ctx, action);
+        let usage = [
+            row(key(Action::Confirm), "replay_pause"),
+            row(
+                format!("{} / {}", key(Action::MenuLeft), key(Action::MenuRight)),
+                "replay_speed",
+            ),
+            row(key(Action::MenuDown), "replay_step"),
+            row(key(Action::Back), "menu_main_menu"),
+        ];
+
+        let mut viewer = Self {
+            playback: [replay.playback(Player::One), replay.playback(Player::Two)],
+            live: Some(live),
+            tick: 0,
+            tick_count: replay.tick_count(),
+            paused: false,
+            speed: NORMAL_SPEED,
+            pending: 0.0,
+            title: TextEntity::anchored(
+                ctx,
+                Text::new(
+                    strings.get("replay_title"),
+                    data.assets.menu_header_font.clone(),
+                ),
+                field,
+                Vec2::new(0.5, 0.3),
+            ),
+            status: TextEntity::new(
+                Text::new("", data.assets.menu_usage_font.clone()),
+                Vec2::zero(),
+            ),
+            usage: TextTable::anchored(
+                ctx,
+                &usage,
+                &data.assets.menu_header_font,
+                field,
+                Vec2::new(0.5, 0.55),
+            ),
+        };
+        viewer.update_status(ctx, data);
+        Ok(viewer)
+    }
+
+    // Advance the replay by one tick, the replay stops at its end
+    fn step(&mut self, data: &mut GameData) {
+        if self.tick >= self.tick_count {
+            self.paused = true;
+            return;
+        }
+
+        let input = DeviceInput::default();
+        let simulation = &data.simulation;
+        let [intent_player_1, intent_player_2] = self
+            .playback
+            .each_mut()
+            .map(|playback| playback.intent(simulation, &input));
+        if let Some(player) =
+            data.simulation
+                .step(SIMULATION_TIMESTEP, intent_player_1, intent_player_2)
+        {
+            data.update_score_text(player);
+        }
+        self.tick += 1;
+    }
+
+    // Show the current tick and speed at the bottom of the screen
+    fn update_status(&mut self, ctx: &mut Context, data: &GameData) {
+        let strings = &data.strings;
+        let mut status = strings
+            .get("replay_status")
+            .replace("{tick}", &self.tick.to_string())
+            .replace("{total}", &self.tick_count.to_string())
+            .replace("{speed}", &format!("x{}", SPEEDS[self.speed]));
+        if self.paused {
+            status = format!("{} {}", status, strings.get("replay_paused"));
+        }
+
+        self.status = TextEntity::anchored(
+            ctx,
+            Text::new(status, data.assets.menu_usage_font.clone()),
+            data.simulation.field(),
+            Vec2::new(0.5, 0.95),
+        );
+    }
+
+    // Action of a click on a line of the usage
+    fn click(&self, position: Vec2<f32>) -> Option<Action> {
+        match self.usage.line_at(position)? {
+            0 => Some(Action::Confirm),
+            1 => Some(Action::MenuRight),
+            2 => Some(Action::MenuDown),
+            _ => Some(Action::Back),
+        }
+    }
+}
+
+impl Scene for ReplayViewer {
+    fn update(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<Transition> {
+        if self.paused {
+            return Ok(Transition::None);
+        }
+
+        // a slow speed skips some updates, a fast one does several ticks per update
+        self.pending += SPEEDS[self.speed];
+        let mut stepped = false;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            self.step(data);
+            stepped = true;
+        }
+        // the paddles and the ball stay where they are until the next tick
+        if !stepped {
+            data.simulation.settle();
+        }
+
+        self.update_status(ctx, data);
+        Ok(Transition::None)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, data: &mut GameData) -> anyhow::Result<()> {
+        data.draw_playfield(ctx);
+        data.draw_match_objects(ctx);
+
+        // the usage will be shown while the replay is paused
+        if self.paused {
+            data.playfield.draw_shade(ctx, SHADE_OPACITY);
+            self.title.draw(ctx);
+            self.usage.draw(ctx);
+        }
+        self.status.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        let action = match data.clicked(ctx, &event) {
+            Some(position) if self.paused => self.click(position),
+            // a click on the running replay pauses it
+            Some(_) => Some(Action::Confirm),
+            None => data.controls.pressed(&event, Scope::Menu),
+        };
+
+        match action {
+            Some(Action::Confirm) => {
+                self.paused = !self.paused;
+                self.pending = 0.0;
+                data.simulation.settle();
+            }
+            Some(Action::MenuLeft) => self.speed = self.speed.saturating_sub(1),
+            Some(Action::MenuRight) => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+            Some(Action::MenuDown) => {
+                // a single tick pauses the replay, so that the next ticks can be followed
+                self.paused = true;
+                self.step(data);
+                data.simulation.settle();
+            }
+            Some(Action::Back) => {
+                // the main menu continues with the simulation of the game
+                if let Some(live) = self.live.take() {
+                    data.simulation = live;
+                }
+                data.fit_field(ctx)?;
+                data.update_score_text(Player::One);
+                data.update_score_text(Player::Two);
+                return Ok(Transition::Reset(Box::new(MainMenu::new(ctx, data))));
+            }
+            _ => return Ok(Transition::None),
+        }
+
+        self.update_status(ctx, data);
+        Ok(Transition::None)
+    }
+}
@@ -0,0 +1,320 @@
+use super::{KeyBindings, MainMenu, Scene, Transition};
+use crate::action::{Action, Scope};
+use crate::config::{DisplayConfig, DisplayMode, GamepadConfig, GamepadSlot, MousePlayer};
+use crate::game::{GameData, TableLine, TextEntity, TextTable};
+use crate::localization::switch_language;
+use tetra::graphics::text::Text;
+use tetra::math::Vec2;
+use tetra::{Context, Event};
+
+/// Frame limits, which can be chosen in the settings, 0 means no limit
+const FRAME_LIMITS: [u32; 6] = [0, 30, 60, 120, 144, 240];
+
+/// Number of entries in the settings screen
+const ENTRY_COUNT: usize = 10;
+
+/// Index of the entry to choose the display mode
+const DISPLAY_MODE_ENTRY: usize = 0;
+
+/// Index of the entry to switch vsync on and off
+const VSYNC_ENTRY: usize = 1;
+
+/// Index of the entry to choose the language
+const LANGUAGE_ENTRY: usize = 3;
+
+/// Index of the entry to assign a gamepad to the left player
+const GAMEPAD_1_ENTRY: usize = 4;
+
+/// Index of the entry to assign a gamepad to the right player
+const GAMEPAD_2_ENTRY: usize = 5;
+
+/// Index of the entry to choose the player, whose paddle follows the mouse
+const MOUSE_ENTRY: usize = 6;
+
+/// Index of the entry to switch the capture of the mouse on and off
+const MOUSE_CAPTURE_ENTRY: usize = 7;
+
+/// Index of the entry to switch the dynamic difficulty of the computer opponents on and off
+const DYNAMIC_DIFFICULTY_ENTRY: usize = 8;
+
+/// Index of the entry to open the key bindings
+pub const CONTROLS_ENTRY: usize = 9;
+
+/// Index of the line of the usage, which goes back to the main menu
+const BACK_LINE: usize = 2;
+
+/// Settings screen to change the display settings, the language, the input devices and the
+/// computer opponents at runtime, the key bindings have their own screen.
+/// Every change will be applied immediately and stored in the configuration file.
+pub struct Settings {
+    header: TextEntity,
+    entries: TextTable,
+    usage: TextTable,
+    selected: usize,
+}
+
+impl Settings {
+    // constructor for the settings screen
+    // - selected is the index of the entry, which will be marked
+    pub fn new(ctx: &mut Context, data: &GameData, selected: usize) -> Self {
+        let field = data.simulation.field();
+        let strings = &data.strings;
+        let controls = &data.controls;
+        let row = |first: Action, second: Action, text: &str| {
+            let keys = format!(
+                "{} / {}",
+                controls.key_name(ctx, first),
+                controls.key_name(ctx, second)
+            );
+            TableLine::Row(keys, strings.get(text).to_owned())
+        };
+        let usage = [
+            row(Action::MenuUp, Action::MenuDown, "settings_choose"),
+            row(Action::MenuLeft, Action::MenuRight, "settings_change"),
+            TableLine::Row(
+                controls.key_name(ctx, Action::Back),
+                strings.get("menu_main_menu").to_owned(),
+            ),
+        ];
+
+        Self {
+            header: TextEntity::anchored(
+                ctx,
+                Text::new(
+                    strings.get("settings_title"),
+                    data.assets.menu_header_font.clone(),
+                ),
+                field,
+                Vec2::new(0.5, 0.1),
+            ),
+            entries: Self::entries(ctx, data, selected),
+            usage: TextTable::anchored(
+                ctx,
+                &usage,
+                &data.assets.menu_usage_font,
+                field,
+                Vec2::new(0.5, 0.85),
+            ),
+            selected,
+        }
+    }
+
+    // Table with the current values, the selected value is enclosed in arrows
+    fn entries(ctx: &mut Context, data: &GameData, selected: usize) -> TextTable {
+        let strings = &data.strings;
+        let display = &data.config.display;
+
+        let mode = match display.mode {
+            DisplayMode::Windowed => strings.get("display_windowed"),
+            DisplayMode::Borderless => strings.get("display_borderless"),
+            DisplayMode::Fullscreen => strings.get("display_fullscreen"),
+        };
+        let switch = |value: bool| match value {
+            true => strings.get("value_on").to_owned(),
+            false => strings.get("value_off").to_owned(),
+        };
+        let frame_limit = match display.frame_limit {
+            0 => strings.get("value_off").to_owned(),
+            limit => format!("{} {}", limit, strings.get("frames_per_second")),
+        };
+        let mouse = match data.config.mouse.player {
+            MousePlayer::None => "mouse_none",
+            MousePlayer::Player1 => "mouse_player_1",
+            MousePlayer::Player2 => "mouse_player_2",
+        };
+        let gamepad = |slot: GamepadSlot| {
+            let name = match slot {
+                GamepadSlot::None => return strings.get("gamepad_none").to_owned(),
+                GamepadSlot::First => strings.get("gamepad_first"),
+                GamepadSlot::Second => strings.get("gamepad_second"),
+            };
+            match data.gamepads.is_connected(slot) {
+                true => name.to_owned(),
+                false => format!("{} {}", name, strings.get("gamepad_missing")),
+            }
+        };
+
+        let values = [
+            ("settings_display_mode", mode.to_owned()),
+            ("settings_vsync", switch(display.vsync)),
+            ("settings_frame_limit", frame_limit),
+            ("settings_language", strings.get("language_name").to_owned()),
+            ("settings_gamepad_1", gamepad(data.config.gamepads.player_1)),
+            ("settings_gamepad_2", gamepad(data.config.gamepads.player_2)),
+            ("settings_mouse", strings.get(mouse).to_owned()),
+            ("settings_mouse_capture", switch(data.config.mouse.relative)),
+            (
+                "settings_dynamic_difficulty",
+                switch(data.config.players.dynamic_difficulty),
+            ),
+            ("settings_controls", "...".to_owned()),
+        ];
+
+        let lines: Vec<TableLine> = values
+            .into_iter()
+            .enumerate()
+            .map(|(index, (label, value))| {
+                let value = match index == selected {
+                    true => format!("< {} >", value),
+                    false => value,
+                };
+                TableLine::Row(strings.get(label).to_owned(), value)
+            })
+            .collect();
+
+        TextTable::anchored(
+            ctx,
+            &lines,
+            &data.assets.menu_header_font,
+            data.simulation.field(),
+            Vec2::new(0.5, 0.45),
+        )
+    }
+
+    // Change the value of the selected display setting
+    // - forward is true for the next value and false for the previous one
+    fn change_display(&self, display: &mut DisplayConfig, forward: bool) {
+        match self.selected {
+            DISPLAY_MODE_ENTRY => {
+                display.mode = if forward {
+                    display.mode.next()
+                } else {
+                    display.mode.previous()
+                }
+            }
+            VSYNC_ENTRY => display.vsync = !display.vsync,
+            _ => {
+                // an unknown limit of the config file continues with the nearest one
+                let count = FRAME_LIMITS.len();
+                let index = match FRAME_LIMITS.binary_search(&display.frame_limit) {
+                    Ok(index) if forward => (index + 1) % count,
+                    Ok(index) => (index + count - 1) % count,
+                    Err(greater) if forward => greater % count,
+                    Err(greater) => greater - 1,
+                };
+                display.frame_limit = FRAME_LIMITS[index];
+            }
+        }
+    }
+
+    // Action of a click, which selects and changes the clicked entry or goes back to the main menu
+    fn click(&mut self, position: Vec2<f32>) -> Option<Action> {
+        if let Some(line) = self.entries.line_at(position) {
+            self.selected = line;
+            return Some(Action::Confirm);
+        }
+
+        match self.usage.line_at(position) {
+            Some(BACK_LINE) => Some(Action::Back),
+            _ => None,
+        }
+    }
+
+    // Assign the next or previous gamepad to the player of the selected entry
+    // - both players may use the same gamepad, e.g. to try it out
+    fn change_gamepads(&self, gamepads: &mut GamepadConfig, forward: bool) {
+        let slot = match self.selected {
+            GAMEPAD_1_ENTRY => &mut gamepads.player_1,
+            _ => &mut gamepads.player_2,
+        };
+        *slot = if forward {
+            slot.next()
+        } else {
+            slot.previous()
+        };
+    }
+}
+
+impl Scene for Settings {
+    fn draw(&mut self, ctx: &mut Context, _data: &mut GameData) -> anyhow::Result<()> {
+        self.header.draw(ctx);
+        self.entries.draw(ctx);
+        self.usage.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        data: &mut GameData,
+        event: Event,
+    ) -> anyhow::Result<Transition> {
+        let action = match data.clicked(ctx, &event) {
+            Some(position) => self.click(position),
+            None => data.controls.pressed(&event, Scope::Menu),
+        };
+
+        match action {
+            Some(Action::MenuUp) => {
+                self.selected = (self.selected + ENTRY_COUNT - 1) % ENTRY_COUNT;
+            }
+            Some(Action::MenuDown) => {
+                self.selected = (self.selected + 1) % ENTRY_COUNT;
+            }
+            Some(Action::MenuLeft | Action::MenuRight | Action::Confirm)
+                if self.selected == CONTROLS_ENTRY =>
+            {
+                return Ok(Transition::Replace(Box::new(KeyBindings::new(ctx, data))));
+            }
+            Some(action @ (Action::MenuLeft | Action::MenuRight | Action::Confirm))
+                if self.selected == LANGUAGE_ENTRY =>
+            {
+                // all texts of this screen have to be rebuilt in the new language
+                let forward = action != Action::MenuLeft;
+                let language = switch_language(data.strings.language(), forward);
+                data.change_language(language)?;
+                *self = Settings::new(ctx, data, self.selected);
+            }
+            Some(action @ (Action::MenuLeft | Action::MenuRight | Action::Confirm))
+                if self.selected == GAMEPAD_1_ENTRY || self.selected == GAMEPAD_2_ENTRY =>
+            {
+                let mut gamepads = data.config.gamepads;
+                self.change_gamepads(&mut gamepads, action != Action::MenuLeft);
+                data.change_gamepads(gamepads);
+            }
+            Some(action @ (Action::MenuLeft | Action::MenuRight | Action::Confirm))
+                if self.selected == MOUSE_ENTRY || self.selected == MOUSE_CAPTURE_ENTRY =>
+            {
+                let mut mouse = data.config.mouse;
+                match self.selected {
+                    MOUSE_ENTRY if action == Action::MenuLeft => {
+                        mouse.player = mouse.player.previous()
+                    }
+                    MOUSE_ENTRY => mouse.player = mouse.player.next(),
+                    _ => mouse.relative = !mouse.relative,
+                }
+                data.change_mouse(mouse);
+            }
+            Some(Action::MenuLeft | Action::MenuRight | Action::Confirm)
+                if self.selected == DYNAMIC_DIFFICULTY_ENTRY =>
+            {
+                let mut players = data.config.players;
+                players.dynamic_difficulty = !players.dynamic_difficulty;
+                data.change_players(players);
+            }
+            Some(action @ (Action::MenuLeft | Action::MenuRight | Action::Confirm)) => {
+                let mut display = data.config.display;
+                self.change_display(&mut display, action != Action::MenuLeft);
+                data.change_display(ctx, display);
+            }
+            // the display mode was switched for all scenes
+            Some(Action::SwitchDisplayMode) => {}
+            Some(Action::Back) => {
+                // the main menu will be rebuilt, in case the language was changed
+                return Ok(Transition::Reset(Box::new(MainMenu::new(ctx, data))));
+            }
+            // show whether the assigned gamepads are connected
+            _ if matches!(
+                event,
+                Event::GamepadAdded { .. } | Event::GamepadRemoved { .. }
+            ) => {}
+            _ => return Ok(Transition::None),
+        }
+
+        // show the current values and the selected entry
+        self.entries = Self::entries(ctx, data, self.selected);
+
+        Ok(Transition::None)
+    }
+}
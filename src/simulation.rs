@@ -0,0 +1,829 @@
+use crate::collision;
+use crate::util::{
+    BALL_ACC, BALL_SPEED_HARD, BALL_SPEED_NORMAL, PADDLE_SPEED_HIGH, PADDLE_SPEED_NORMAL,
+    PADDLE_SPIN, SCORE_LIMIT,
+};
+use anyhow::bail;
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use vek::Vec2;
+
+/// Distance between a paddle and the left or right edge of the playfield
+const PADDLE_MARGIN: f32 = 16.0;
+
+/// Distance a paddle has to keep to the top and the bottom of the playfield
+const PADDLE_LIMIT: f32 = 10.0;
+
+/// Distance the ball has to travel behind a paddle until a point is scored,
+/// to achieve a small delay until the ball comes into play again
+const OUT_OF_FIELD: f32 = 100.0;
+
+/// Maximum number of paddle contacts the ball can have within one tick
+const MAX_CONTACTS_PER_TICK: usize = 4;
+
+/// The two players of a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Player {
+    /// Left hand side
+    One,
+    /// Right hand side
+    Two,
+}
+
+impl Player {
+    // The player on the other side
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    // Position of the player in arrays of both players, 0 for player 1 and 1 for player 2
+    pub fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+}
+
+/// The game play modes which can be chosen in the main menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GameMode {
+    Normal,
+    Hard,
+}
+
+impl GameMode {
+    // GETTER
+    pub fn paddle_speed(self, rules: &Rules) -> f32 {
+        match self {
+            GameMode::Normal => rules.paddle_speed_normal,
+            GameMode::Hard => rules.paddle_speed_hard,
+        }
+    }
+
+    pub fn ball_speed(self, rules: &Rules) -> f32 {
+        match self {
+            GameMode::Normal => rules.ball_speed_normal,
+            GameMode::Hard => rules.ball_speed_hard,
+        }
+    }
+}
+
+/// Speeds and limits of a match, all speeds are given in units per second
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub paddle_speed_normal: f32,
+    pub paddle_speed_hard: f32,
+    pub ball_speed_normal: f32,
+    pub ball_speed_hard: f32,
+    /// Maximum vertical speed a paddle adds to the ball, if the ball hits the paddle's edge
+    pub paddle_spin: f32,
+    /// Horizontal speed the ball gains with every paddle hit
+    pub ball_acc: f32,
+    /// Points a player needs to win the match
+    pub score_limit: u8,
+}
+
+impl Rules {
+    // Check that all speeds are positive numbers and that a match can be won
+    // - the errors name the keys of the rules in the configuration file
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let speeds = [
+            ("rules.paddle_speed_normal", self.paddle_speed_normal),
+            ("rules.paddle_speed_hard", self.paddle_speed_hard),
+            ("rules.ball_speed_normal", self.ball_speed_normal),
+            ("rules.ball_speed_hard", self.ball_speed_hard),
+        ];
+        for (key, value) in speeds {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("'{}' must be greater than 0, got {}", key, value);
+            }
+        }
+
+        let factors = [
+            ("rules.paddle_spin", self.paddle_spin),
+            ("rules.ball_acc", self.ball_acc),
+        ];
+        for (key, value) in factors {
+            if !value.is_finite() || value < 0.0 {
+                bail!("'{}' must not be negative, got {}", key, value);
+            }
+        }
+
+        if self.score_limit == 0 {
+            bail!("'rules.score_limit' must be at least 1");
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            paddle_speed_normal: PADDLE_SPEED_NORMAL,
+            paddle_speed_hard: PADDLE_SPEED_HIGH,
+            ball_speed_normal: BALL_SPEED_NORMAL,
+            ball_speed_hard: BALL_SPEED_HARD,
+            paddle_spin: PADDLE_SPIN,
+            ball_acc: BALL_ACC,
+            score_limit: SCORE_LIMIT,
+        }
+    }
+}
+
+/// The movement a player wants to do with the paddle within one tick
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PaddleIntent {
+    /// -1.0 moves the paddle up with full speed, 1.0 moves it down with full speed
+    pub axis: f32,
+}
+
+impl PaddleIntent {
+    // Constructor for digital input like two keys or a d-pad
+    pub fn from_buttons(up: bool, down: bool) -> Self {
+        let mut axis = 0.0;
+        if up {
+            axis -= 1.0;
+        }
+        if down {
+            axis += 1.0;
+        }
+
+        Self { axis }
+    }
+
+    // Constructor for analog input like a stick, which moves the paddle proportional to its position
+    // - positions within the deadzone are ignored, the remaining range will be rescaled,
+    // so that the speed of the paddle starts at zero behind the deadzone
+    pub fn from_axis(position: f32, deadzone: f32) -> Self {
+        let magnitude = position.abs();
+        if magnitude <= deadzone || deadzone >= 1.0 {
+            return Self::default();
+        }
+
+        let axis = ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0) * position.signum();
+        Self { axis }
+    }
+
+    // Constructor for input with a target position like a mouse, which moves the paddle towards it
+    // - distance is the way from the paddle to the target, max_distance is the way the paddle
+    // can go within one tick, so that the paddle is never faster than with the keyboard
+    pub fn towards(distance: f32, max_distance: f32) -> Self {
+        if max_distance <= 0.0 {
+            return Self::default();
+        }
+
+        Self {
+            axis: (distance / max_distance).clamp(-1.0, 1.0),
+        }
+    }
+
+    // Combine the intents of several inputs of the same player, e.g. keyboard and gamepad
+    pub fn combine(self, other: PaddleIntent) -> Self {
+        Self {
+            axis: (self.axis + other.axis).clamp(-1.0, 1.0),
+        }
+    }
+}
+
+/// Axis aligned object on the playfield, like a paddle or the ball
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Body {
+    /// Position of the upper left corner (2D coordinates - x and y)
+    pub position: Vec2<f32>,
+    /// Position at the beginning of the last tick, used to interpolate the drawing
+    pub previous_position: Vec2<f32>,
+    /// Width and height
+    pub size: Vec2<f32>,
+    /// velocity in units per second
+    pub velocity: Vec2<f32>,
+}
+
+impl Body {
+    // Constructor for a body without a velocity
+    pub fn new(position: Vec2<f32>, size: Vec2<f32>) -> Self {
+        Self {
+            position,
+            previous_position: position,
+            size,
+            velocity: Vec2::zero(),
+        }
+    }
+
+    // Move the body to a new position without interpolating between the old and the new one
+    pub fn teleport(&mut self, position: Vec2<f32>) {
+        self.position = position;
+        self.previous_position = position;
+    }
+
+    // Position between the last and the current tick
+    // - blend is the progress towards the next tick, as a number between 0.0 and 1.0
+    pub fn interpolated_position(&self, blend: f32) -> Vec2<f32> {
+        Vec2::lerp(self.previous_position, self.position, blend)
+    }
+
+    pub fn centre(&self) -> Vec2<f32> {
+        self.position + self.size / 2.0
+    }
+
+    // Check if the rectangles of both bodies overlap
+    pub fn intersects(&self, other: &Body) -> bool {
+        self.position.x < other.position.x + other.size.x
+            && self.position.x + self.size.x > other.position.x
+            && self.position.y < other.position.y + other.size.y
+            && self.position.y + self.size.y > other.position.y
+    }
+
+    // Check if the rectangles of both bodies overlap or touch each other
+    pub fn touches(&self, other: &Body) -> bool {
+        self.position.x <= other.position.x + other.size.x
+            && self.position.x + self.size.x >= other.position.x
+            && self.position.y <= other.position.y + other.size.y
+            && self.position.y + self.size.y >= other.position.y
+    }
+}
+
+/// Renderer independent state of a match, which holds the paddles, the ball and the score.
+/// It will be advanced tick by tick with the intents of both players.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// Width and height of the playfield
+    field: Vec2<f32>,
+    rules: Rules,
+    paddle_speed: f32,
+    pub player_1: Body,
+    pub player_2: Body,
+    pub ball: Body,
+    pub score_player_1: u8,
+    pub score_player_2: u8,
+    /// Paddle the ball is currently in contact with, which has already been responded to
+    contact: Option<Player>,
+    /// Seed of the current match, which makes all serves reproducible
+    seed: u64,
+    /// Source of all randomness within a match
+    rng: ChaCha8Rng,
+}
+
+impl Simulation {
+    // Constructor for a match with centered paddles and a ball at rest
+    pub fn new(
+        field: Vec2<f32>,
+        paddle_size: Vec2<f32>,
+        ball_size: Vec2<f32>,
+        rules: Rules,
+    ) -> Self {
+        let mut simulation = Self {
+            field,
+            rules,
+            paddle_speed: rules.paddle_speed_normal,
+            player_1: Body::new(Vec2::zero(), paddle_size),
+            player_2: Body::new(Vec2::zero(), paddle_size),
+            ball: Body::new(Vec2::zero(), ball_size),
+            score_player_1: 0,
+            score_player_2: 0,
+            contact: None,
+            seed: 0,
+            rng: ChaCha8Rng::seed_from_u64(0),
+        };
+        simulation.reset();
+
+        simulation
+    }
+
+    // GETTER
+    pub fn field(&self) -> Vec2<f32> {
+        self.field
+    }
+
+    pub fn paddle(&self, player: Player) -> &Body {
+        match player {
+            Player::One => &self.player_1,
+            Player::Two => &self.player_2,
+        }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    // Highest and lowest position of a paddle's centre, which the paddle can reach
+    pub fn paddle_centre_range(&self, player: Player) -> (f32, f32) {
+        let half_height = self.paddle(player).size.y / 2.0;
+        (
+            PADDLE_LIMIT + half_height,
+            self.field.y - PADDLE_LIMIT - half_height,
+        )
+    }
+
+    // Speed of the paddles within the current match
+    pub fn paddle_speed(&self) -> f32 {
+        self.paddle_speed
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn score(&self, player: Player) -> u8 {
+        match player {
+            Player::One => self.score_player_1,
+            Player::Two => self.score_player_2,
+        }
+    }
+
+    // Returns the player who reached the score limit, if any
+    pub fn winner(&self) -> Option<Player> {
+        if self.score_player_1 >= self.rules.score_limit {
+            Some(Player::One)
+        } else if self.score_player_2 >= self.rules.score_limit {
+            Some(Player::Two)
+        } else {
+            None
+        }
+    }
+
+    // Check if a match was started and has no winner yet
+    pub fn is_running(&self) -> bool {
+        self.ball.velocity.x != 0.0 && self.winner().is_none()
+    }
+
+    // Reset scores, paddles and the ball to the initial state of a match
+    pub fn reset(&mut self) {
+        self.score_player_1 = 0;
+        self.score_player_2 = 0;
+
+        self.player_1.teleport(Vec2::new(
+            PADDLE_MARGIN,
+            (self.field.y - self.player_1.size.y) / 2.0,
+        ));
+        self.player_2.teleport(Vec2::new(
+            self.field.x - self.player_2.size.x - PADDLE_MARGIN,
+            (self.field.y - self.player_2.size.y) / 2.0,
+        ));
+
+        self.center_ball();
+        self.ball.velocity = Vec2::zero();
+        self.contact = None;
+    }
+
+    // Start a new match with the speeds of the given game play mode
+    // - the same seed always leads to the same sequence of serves
+    pub fn start(&mut self, mode: GameMode, seed: u64) {
+        self.reset();
+        self.paddle_speed = mode.paddle_speed(&self.rules);
+        self.seed = seed;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+
+        // move the ball randomly to player one or player two
+        let speed = mode.ball_speed(&self.rules);
+        self.ball.velocity = match self.rng.random() {
+            true => Vec2::new(-speed, 0.0),
+            false => Vec2::new(speed, 0.0),
+        };
+    }
+
+    // Forget the last tick, so that the drawing shows the current positions
+    // - used while the match is not advanced, e.g. when it's paused
+    pub fn settle(&mut self) {
+        for body in [&mut self.player_1, &mut self.player_2, &mut self.ball] {
+            body.previous_position = body.position;
+        }
+    }
+
+    // Advance the match by one tick with the duration dt in seconds
+    // - returns the player who scored within this tick
+    pub fn step(
+        &mut self,
+        dt: f32,
+        player_1: PaddleIntent,
+        player_2: PaddleIntent,
+    ) -> Option<Player> {
+        self.settle();
+
+        if self.winner().is_some() {
+            return None;
+        }
+
+        Self::move_paddle(
+            &mut self.player_1,
+            player_1,
+            self.paddle_speed,
+            dt,
+            self.field.y,
+            &self.ball,
+        );
+        Self::move_paddle(
+            &mut self.player_2,
+            player_2,
+            self.paddle_speed,
+            dt,
+            self.field.y,
+            &self.ball,
+        );
+
+        self.move_ball(dt);
+
+        // check if the ball hit the top or the bottom of the playfield
+        if let Some((correction, normal)) = collision::border_penetration(&self.ball, self.field.y)
+        {
+            // if this is the case -> push the ball back inside and revert the ball direction
+            self.ball.position += correction;
+            self.ball.velocity = collision::reflect(self.ball.velocity, normal);
+
+            // the border may have pushed the ball into a paddle
+            for player in [Player::One, Player::Two] {
+                self.squeeze(player);
+            }
+        }
+
+        self.check_score()
+    }
+
+    // Move the ball on the playfield with its velocity and respond to every paddle it hits.
+    // The movement is swept, so that the ball cannot tunnel through a paddle at high speed.
+    fn move_ball(&mut self, dt: f32) {
+        // a paddle which moved into the ball pushes it out again
+        for player in [Player::One, Player::Two] {
+            if let Some((correction, normal)) =
+                collision::penetration(&self.ball, self.paddle(player))
+            {
+                self.ball.position += correction;
+                self.paddle_contact(player, normal);
+            }
+        }
+
+        let mut remaining = dt;
+        for _ in 0..MAX_CONTACTS_PER_TICK {
+            let displacement = self.ball.velocity * remaining;
+
+            // find the paddle which will be hit first, except the one the ball already touches
+            let hit = [Player::One, Player::Two]
+                .into_iter()
+                .filter(|player| self.contact != Some(*player))
+                .filter_map(|player| {
+                    collision::sweep(&self.ball, displacement, self.paddle(player))
+                        .map(|contact| (player, contact))
+                })
+                .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time));
+
+            match hit {
+                Some((player, contact)) => {
+                    // move the ball to the point of impact and continue with the new velocity
+                    self.ball.position += displacement * contact.time;
+                    remaining *= 1.0 - contact.time;
+                    self.paddle_contact(player, contact.normal);
+                }
+                None => {
+                    self.ball.position += displacement;
+                    break;
+                }
+            }
+        }
+
+        // the contact ends, as soon as the ball does not touch the paddle anymore
+        if let Some(player) = self.contact {
+            if !self.ball.touches(self.paddle(player)) {
+                self.contact = None;
+            }
+        }
+    }
+
+    // Respond to a contact between the ball and the paddle of the given player,
+    // but only once for as long as the ball touches the paddle.
+    // - normal is the normal of the paddle face which was hit
+    fn paddle_contact(&mut self, player: Player, normal: Vec2<f32>) {
+        if self.contact == Some(player) {
+            return;
+        }
+        self.contact = Some(player);
+
+        let paddle = *self.paddle(player);
+
+        // the front or the back face was hit
+        if normal.x != 0.0 {
+            // Increase the ball's velocity, then flip it away from the paddle.
+            self.ball.velocity.x = (self.ball.velocity.x.abs() + self.rules.ball_acc) * normal.x;
+
+            // Calculate the offset between the paddle and the ball, as a number between
+            // -1.0 and 1.0.
+            let offset = (paddle.centre().y - self.ball.centre().y) / paddle.size.y;
+
+            // Apply the spin to the ball.
+            self.ball.velocity.y += self.rules.paddle_spin * -offset;
+        }
+
+        // the top or the bottom face was hit
+        if normal.y != 0.0 {
+            // bounce off vertically, but at least as fast as the paddle moves towards the ball,
+            // otherwise the paddle would catch up with the ball again
+            let reflected = collision::reflect(self.ball.velocity, Vec2::new(0.0, normal.y));
+            self.ball.velocity.y =
+                normal.y * (reflected.y * normal.y).max(paddle.velocity.y * normal.y);
+        }
+    }
+
+    // Respond to a ball, which was pushed into the top or the bottom face of a paddle by a border.
+    // The ball is caught between the border and the paddle, so it will be put back onto the face
+    // and keeps moving vertically between both, until it leaves the paddle horizontally.
+    // - the horizontal velocity stays the same, only a paddle's front or back adds speed
+    fn squeeze(&mut self, player: Player) {
+        let paddle = *self.paddle(player);
+        if !self.ball.intersects(&paddle) {
+            return;
+        }
+
+        let speed = self.ball.velocity.y.abs();
+        if self.ball.centre().y < paddle.centre().y {
+            self.ball.position.y = paddle.position.y - self.ball.size.y;
+            self.ball.velocity.y = -speed;
+        } else {
+            self.ball.position.y = paddle.position.y + paddle.size.y;
+            self.ball.velocity.y = speed;
+        }
+        self.contact = Some(player);
+    }
+
+    // Move a paddle according to the intent, as long as it stays in the playfield
+    // - a paddle stops at a ball between itself and the border, because the ball needs room
+    // to get out there
+    fn move_paddle(
+        paddle: &mut Body,
+        intent: PaddleIntent,
+        speed: f32,
+        dt: f32,
+        field_height: f32,
+        ball: &Body,
+    ) {
+        // define game border (top and bottom)
+        let upper_limit = PADDLE_LIMIT;
+        let lower_limit = field_height - paddle.size.y - PADDLE_LIMIT;
+
+        if (intent.axis < 0.0 && paddle.position.y > upper_limit)
+            || (intent.axis > 0.0 && paddle.position.y < lower_limit)
+        {
+            paddle.velocity.y = intent.axis.clamp(-1.0, 1.0) * speed;
+            paddle.position.y += paddle.velocity.y * dt;
+        } else {
+            paddle.velocity.y = 0.0;
+        }
+
+        let above_or_below = ball.position.x < paddle.position.x + paddle.size.x
+            && ball.position.x + ball.size.x > paddle.position.x;
+        if !above_or_below {
+            return;
+        }
+        let start = paddle.previous_position.y;
+        let stop = if paddle.velocity.y < 0.0 && ball.centre().y < paddle.centre().y {
+            // the paddle keeps the height of the ball to the top border
+            paddle.position.y.max(ball.size.y.min(start))
+        } else if paddle.velocity.y > 0.0 && ball.centre().y > paddle.centre().y {
+            // the paddle keeps the height of the ball to the bottom border
+            let limit = field_height - ball.size.y - paddle.size.y;
+            paddle.position.y.min(limit.max(start))
+        } else {
+            return;
+        };
+        if stop != paddle.position.y {
+            paddle.position.y = stop;
+            paddle.velocity.y = 0.0;
+        }
+    }
+
+    // Check if the ball goes out of the playfield and if this the case,
+    // increment the score counter for the right player and repositioning the ball.
+    fn check_score(&mut self) -> Option<Player> {
+        let scorer = if self.ball.position.x > self.field.x + OUT_OF_FIELD {
+            // Player 1 (left hand side) gets one point
+            self.score_player_1 += 1;
+            Player::One
+        } else if self.ball.position.x < -OUT_OF_FIELD {
+            // Player 2 (right hand side) gets one point
+            self.score_player_2 += 1;
+            Player::Two
+        } else {
+            return None;
+        };
+
+        self.center_ball();
+        self.contact = None;
+
+        // move the ball randomly to player one or player two, the current ball speed will be kept
+        let speed = self.ball.velocity.x.abs();
+        self.ball.velocity = match self.rng.random() {
+            true => Vec2::new(-speed, 0.0),
+            false => Vec2::new(speed, 0.0),
+        };
+
+        Some(scorer)
+    }
+
+    fn center_ball(&mut self) {
+        self.ball.teleport((self.field - self.ball.size) / 2.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{BALL_SIZE, PADDLE_SIZE, SIMULATION_TIMESTEP, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+    /// Longest time in ticks a test waits for a point
+    const MAX_TICKS: usize = 10_000;
+
+    // Simulation of a match on the default playfield with the given rules
+    fn simulation(rules: Rules) -> Simulation {
+        Simulation::new(
+            Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+            PADDLE_SIZE,
+            BALL_SIZE,
+            rules,
+        )
+    }
+
+    // Advance the match with resting paddles until a player scores
+    fn play_until_point(simulation: &mut Simulation) -> Option<Player> {
+        (0..MAX_TICKS).find_map(|_| {
+            simulation.step(
+                SIMULATION_TIMESTEP,
+                PaddleIntent::default(),
+                PaddleIntent::default(),
+            )
+        })
+    }
+
+    // Move the ball above both paddles, so that it passes them
+    fn serve_above_paddles(simulation: &mut Simulation) {
+        let x = simulation.ball.position.x;
+        simulation.ball.teleport(Vec2::new(x, PADDLE_LIMIT));
+    }
+
+    #[test]
+    fn serve_scores_for_the_player_on_the_other_side() {
+        for seed in 0..8 {
+            let mut simulation = simulation(Rules::default());
+            simulation.start(GameMode::Normal, seed);
+            serve_above_paddles(&mut simulation);
+
+            let expected = match simulation.ball.velocity.x < 0.0 {
+                true => Player::Two,
+                false => Player::One,
+            };
+            assert_eq!(play_until_point(&mut simulation), Some(expected));
+            assert_eq!(simulation.score(expected), 1);
+            assert_eq!(simulation.score(expected.opponent()), 0);
+        }
+    }
+
+    #[test]
+    fn paddle_return_adds_ball_acc_and_spin() {
+        let rules = Rules::default();
+        let mut simulation = simulation(rules);
+        simulation.start(GameMode::Normal, 0);
+
+        // the ball hits the front of paddle 2 a quarter of its height below the centre
+        let paddle = simulation.player_2;
+        let offset = paddle.size.y / 4.0;
+        simulation.ball.teleport(Vec2::new(
+            paddle.position.x - simulation.ball.size.x - 1.0,
+            paddle.centre().y + offset - simulation.ball.size.y / 2.0,
+        ));
+        simulation.ball.velocity = Vec2::new(rules.ball_speed_normal, 0.0);
+        simulation.step(
+            SIMULATION_TIMESTEP,
+            PaddleIntent::default(),
+            PaddleIntent::default(),
+        );
+
+        let expected = Vec2::new(
+            -(rules.ball_speed_normal + rules.ball_acc),
+            rules.paddle_spin * offset / paddle.size.y,
+        );
+        assert_eq!(simulation.ball.velocity, expected);
+    }
+
+    #[test]
+    fn match_stops_at_score_limit() {
+        let mut simulation = simulation(Rules {
+            score_limit: 3,
+            ..Rules::default()
+        });
+        simulation.start(GameMode::Normal, 1);
+
+        while simulation.winner().is_none() {
+            serve_above_paddles(&mut simulation);
+            assert!(play_until_point(&mut simulation).is_some());
+        }
+
+        let winner = simulation.winner().unwrap();
+        assert_eq!(simulation.score(winner), 3);
+        assert!(simulation.score(winner.opponent()) < 3);
+        assert!(!simulation.is_running());
+
+        // the ball does not move anymore, so there are no further points
+        serve_above_paddles(&mut simulation);
+        let ball = simulation.ball;
+        assert_eq!(play_until_point(&mut simulation), None);
+        assert_eq!(simulation.ball, ball);
+    }
+
+    #[test]
+    fn fast_ball_does_not_tunnel_through_paddles() {
+        let mut simulation = simulation(Rules {
+            ball_speed_normal: 6000.0,
+            ..Rules::default()
+        });
+        simulation.start(GameMode::Normal, 0);
+
+        // the ball moves more than twice its width and the width of a paddle within one tick
+        let displacement = simulation.ball.velocity.x.abs() * SIMULATION_TIMESTEP;
+        assert!(displacement > 2.0 * (simulation.ball.size.x + simulation.player_1.size.x));
+
+        // the ball bounces between the resting paddles for ten seconds without a point
+        let left = simulation.player_1.position.x + simulation.player_1.size.x;
+        let right = simulation.player_2.position.x - simulation.ball.size.x;
+        for _ in 0..600 {
+            let scored = simulation.step(
+                SIMULATION_TIMESTEP,
+                PaddleIntent::default(),
+                PaddleIntent::default(),
+            );
+            assert_eq!(scored, None);
+            assert!((left..=right).contains(&simulation.ball.position.x));
+        }
+    }
+
+    #[test]
+    fn paddle_held_against_ball_responds_once() {
+        let mut simulation = simulation(Rules::default());
+        simulation.start(GameMode::Normal, 0);
+
+        // the ball slides slowly over the top of paddle 2, which carries it up against the border
+        let x = simulation.player_2.position.x;
+        simulation.player_2.teleport(Vec2::new(x, 60.0));
+        let size = simulation.ball.size;
+        simulation
+            .ball
+            .teleport(Vec2::new(x + 1.0, 60.0 - size.y - 0.5));
+        let velocity_x = -120.0;
+        simulation.ball.velocity = Vec2::new(velocity_x, 0.0);
+
+        let mut contacts = 0;
+        for _ in 0..30 {
+            let contact = simulation.contact;
+            simulation.step(
+                SIMULATION_TIMESTEP,
+                PaddleIntent::default(),
+                PaddleIntent { axis: -1.0 },
+            );
+            if contact.is_none() && simulation.contact == Some(Player::Two) {
+                contacts += 1;
+            }
+
+            // the top face never changes the horizontal speed and the ball stays out of the paddle
+            assert_eq!(simulation.ball.velocity.x, velocity_x);
+            assert!(!simulation.ball.intersects(&simulation.player_2));
+        }
+
+        // the squeezed ball was held once and left the paddle with its vertical speed
+        assert_eq!(contacts, 1);
+        let ball = &simulation.ball;
+        assert!(ball.position.x + size.x < simulation.player_2.position.x);
+        assert!(ball.position.y > 0.0);
+        assert_eq!(ball.velocity.y.abs(), simulation.paddle_speed());
+    }
+
+    #[test]
+    fn ball_on_paddle_corner_gets_one_response() {
+        let rules = Rules::default();
+        let mut simulation = simulation(rules);
+        simulation.start(GameMode::Normal, 0);
+
+        // the ball flies diagonally onto the upper front corner of paddle 2
+        let paddle = simulation.player_2;
+        let size = simulation.ball.size;
+        simulation.ball.teleport(paddle.position - size - 2.0);
+        simulation.ball.velocity = Vec2::new(rules.ball_speed_normal, rules.ball_speed_normal);
+
+        for _ in 0..10 {
+            simulation.step(
+                SIMULATION_TIMESTEP,
+                PaddleIntent::default(),
+                PaddleIntent::default(),
+            );
+        }
+
+        // the ball is reflected on both axes and gained the acceleration only once
+        let velocity = simulation.ball.velocity;
+        assert_eq!(velocity.x, -(rules.ball_speed_normal + rules.ball_acc));
+        assert!(velocity.y < 0.0);
+        assert!(simulation.ball.position.x + size.x < paddle.position.x);
+        assert!(simulation.ball.position.y + size.y < paddle.position.y);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::simulation::{Body, Player, Simulation};
+use vek::Vec2;
+
+/// Arrival of the ball at a vertical line, e.g. the face of a paddle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossing {
+    /// Height of the ball's centre
+    pub y: f32,
+    /// Seconds until the ball arrives
+    pub time: f32,
+    /// Velocity of the ball on arrival, after all reflections on the borders
+    pub velocity: Vec2<f32>,
+}
+
+// Follow the ball until its leading edge reaches the line at face_x
+// - the ball will be reflected by the top and the bottom border like in the simulation,
+// so the path can be unfolded into a straight line and folded back into the playfield
+// - returns nothing if the ball does not move towards the line
+pub fn crossing(ball: &Body, face_x: f32, field_height: f32) -> Option<Crossing> {
+    let edge = if ball.velocity.x > 0.0 {
+        ball.position.x + ball.size.x
+    } else {
+        ball.position.x
+    };
+    let time = (face_x - edge) / ball.velocity.x;
+    if !time.is_finite() || time < 0.0 {
+        return None;
+    }
+
+    // every reflection flips the vertical direction of the ball
+    let range = field_height - ball.size.y;
+    let unfolded = ball.position.y + ball.velocity.y * time;
+    let (y, direction) = if range > 0.0 {
+        let reflections = (unfolded / range).floor();
+        let rest = unfolded - reflections * range;
+        match reflections.rem_euclid(2.0) == 0.0 {
+            true => (rest, 1.0),
+            false => (range - rest, -1.0),
+        }
+    } else {
+        (0.0, 1.0)
+    };
+
+    Some(Crossing {
+        y: y + ball.size.y / 2.0,
+        time,
+        velocity: Vec2::new(ball.velocity.x, ball.velocity.y * direction),
+    })
+}
+
+// Position of the paddle face, which will be hit by the ball
+pub fn face_x(simulation: &Simulation, player: Player) -> f32 {
+    let paddle = simulation.paddle(player);
+    match player {
+        Player::One => paddle.position.x + paddle.size.x,
+        Player::Two => paddle.position.x,
+    }
+}
+
+// Ball at the moment it reaches the paddle face of the given player
+fn ball_at(simulation: &Simulation, player: Player, crossing: &Crossing) -> Body {
+    let ball = &simulation.ball;
+    let x = match player {
+        Player::One => face_x(simulation, player),
+        Player::Two => face_x(simulation, player) - ball.size.x,
+    };
+
+    let mut body = Body::new(Vec2::new(x, crossing.y - ball.size.y / 2.0), ball.size);
+    body.velocity = crossing.velocity;
+    body
+}
+
+// Ball after it was hit by the paddle of the given player
+// - offset is the height of the ball above the centre of the paddle relative to the paddle's
+// height, which makes the spin like in the simulation
+pub fn returned(simulation: &Simulation, player: Player, crossing: &Crossing, offset: f32) -> Body {
+    let rules = simulation.rules();
+    let mut ball = ball_at(simulation, player, crossing);
+    let direction = match player {
+        Player::One => 1.0,
+        Player::Two => -1.0,
+    };
+
+    ball.velocity.x = (ball.velocity.x.abs() + rules.ball_acc) * direction;
+    ball.velocity.y += rules.paddle_spin * -offset;
+    ball
+}
+
+// Predict where the ball will reach the paddle of the given player
+// - a ball, which moves to the other player, is expected to be returned with the given change
+// of its vertical speed, which is 0.0 for a return without spin
+// - returns nothing if the ball already passed the paddle
+pub fn arrival(simulation: &Simulation, player: Player, return_spin: f32) -> Option<Crossing> {
+    let field_height = simulation.field().y;
+    let ball = &simulation.ball;
+    let approaching = match player {
+        Player::One => ball.velocity.x < 0.0,
+        Player::Two => ball.velocity.x > 0.0,
+    };
+
+    if approaching {
+        return crossing(ball, face_x(simulation, player), field_height);
+    }
+
+    let other = player.opponent();
+    let first = crossing(ball, face_x(simulation, other), field_height)?;
+    let mut ball = returned(simulation, other, &first, 0.0);
+    ball.velocity.y += return_spin;
+    let second = crossing(&ball, face_x(simulation, player), field_height)?;
+
+    Some(Crossing {
+        time: first.time + second.time,
+        ..second
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{GameMode, PaddleIntent, Rules};
+    use crate::util::{BALL_SIZE, PADDLE_SIZE, SIMULATION_TIMESTEP, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+    /// Largest difference between a prediction and the simulation, caused by rounding
+    const TOLERANCE: f32 = 0.05;
+
+    // Running match on the default playfield, whose ball is placed by the test
+    fn simulation() -> Simulation {
+        let mut simulation = Simulation::new(
+            Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+            PADDLE_SIZE,
+            BALL_SIZE,
+            Rules::default(),
+        );
+        simulation.start(GameMode::Normal, 0);
+        simulation
+    }
+
+    // Advance the match by one tick with resting paddles
+    fn step(simulation: &mut Simulation) {
+        simulation.step(
+            SIMULATION_TIMESTEP,
+            PaddleIntent::default(),
+            PaddleIntent::default(),
+        );
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < TOLERANCE,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn crossing_matches_simulation_over_several_bounces() {
+        let mut simulation = simulation();
+        let field_height = simulation.field().y;
+        simulation.ball.teleport(Vec2::new(100.0, 100.0));
+        simulation.ball.velocity = Vec2::new(150.0, 700.0);
+        let start = simulation.ball;
+        let face = face_x(&simulation, Player::Two);
+
+        let mut bounces = 0;
+        let mut elapsed = 0.0;
+        while simulation.ball.position.x + simulation.ball.size.x + 150.0 * SIMULATION_TIMESTEP
+            < face
+        {
+            let previous = simulation.ball.velocity.y;
+            step(&mut simulation);
+            elapsed += SIMULATION_TIMESTEP;
+            if simulation.ball.velocity.y != previous {
+                bounces += 1;
+            }
+
+            // the line the ball reached within this tick
+            let edge = simulation.ball.position.x + simulation.ball.size.x;
+            let predicted = crossing(&start, edge, field_height).unwrap();
+            assert_close(predicted.y, simulation.ball.centre().y);
+            assert_close(predicted.time, elapsed);
+            assert_eq!(predicted.velocity, simulation.ball.velocity);
+        }
+        assert!(bounces >= 3, "only {} bounces", bounces);
+    }
+
+    #[test]
+    fn crossing_ignores_ball_moving_away() {
+        let mut ball = Body::new(Vec2::new(100.0, 100.0), BALL_SIZE);
+        ball.velocity = Vec2::new(-150.0, 50.0);
+        assert_eq!(crossing(&ball, 600.0, WINDOW_HEIGHT), None);
+
+        ball.velocity = Vec2::new(0.0, 50.0);
+        assert_eq!(crossing(&ball, 600.0, WINDOW_HEIGHT), None);
+    }
+
+    #[test]
+    fn returned_matches_spin_of_paddle_contact() {
+        for offset in [-0.4, -0.1, 0.0, 0.25, 0.45] {
+            let mut simulation = simulation();
+            let field_height = simulation.field().y;
+            let paddle = simulation.player_2;
+
+            // the ball reaches the front of paddle 2 at the given offset within the next tick
+            let height = paddle.centre().y - offset * paddle.size.y;
+            simulation.ball.teleport(Vec2::new(
+                paddle.position.x - simulation.ball.size.x - 1.0,
+                height - simulation.ball.size.y / 2.0 - 0.1,
+            ));
+            simulation.ball.velocity = Vec2::new(300.0, 30.0);
+
+            let arrival = crossing(
+                &simulation.ball,
+                face_x(&simulation, Player::Two),
+                field_height,
+            )
+            .unwrap();
+            let relative = (paddle.centre().y - arrival.y) / paddle.size.y;
+            let expected = returned(&simulation, Player::Two, &arrival, relative);
+
+            step(&mut simulation);
+            assert_close(simulation.ball.velocity.x, expected.velocity.x);
+            assert_close(simulation.ball.velocity.y, expected.velocity.y);
+        }
+    }
+
+    #[test]
+    fn arrival_follows_ball_returned_by_opponent() {
+        let mut simulation = simulation();
+        let field_height = simulation.field().y;
+        simulation.ball.teleport(Vec2::new(200.0, 150.0));
+        simulation.ball.velocity = Vec2::new(300.0, 250.0);
+
+        // put paddle 2 into the path of the ball, so that the ball gets some spin
+        let first = crossing(
+            &simulation.ball,
+            face_x(&simulation, Player::Two),
+            field_height,
+        )
+        .unwrap();
+        let x = simulation.player_2.position.x;
+        let y = first.y - 20.0 - simulation.player_2.size.y / 2.0;
+        simulation.player_2.teleport(Vec2::new(x, y));
+
+        let offset = (simulation.player_2.centre().y - first.y) / simulation.player_2.size.y;
+        let spin = simulation.rules().paddle_spin * -offset;
+        let predicted = arrival(&simulation, Player::One, spin).unwrap();
+
+        // follow the ball until it is one tick away from paddle 1
+        let face = face_x(&simulation, Player::One);
+        let mut elapsed = 0.0;
+        while simulation.ball.velocity.x > 0.0
+            || simulation.ball.position.x + simulation.ball.velocity.x * SIMULATION_TIMESTEP > face
+        {
+            step(&mut simulation);
+            elapsed += SIMULATION_TIMESTEP;
+        }
+
+        let rest = crossing(&simulation.ball, face, field_height).unwrap();
+        assert_close(predicted.y, rest.y);
+        assert_close(predicted.time, elapsed + rest.time);
+        assert_eq!(predicted.velocity.x.signum(), -1.0);
+        assert_close(predicted.velocity.y, rest.velocity.y);
+
+        // an approaching ball is predicted without a return
+        let direct = crossing(&simulation.ball, face, field_height);
+        assert_eq!(arrival(&simulation, Player::One, spin), direct);
+    }
+}
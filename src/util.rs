@@ -1,67 +1,45 @@
-use tetra::math::Vec2;
+use vek::Vec2;
 
 pub const WINDOW_WIDTH: f32 = 640.0;
 pub const WINDOW_HEIGHT: f32 = 480.0;
-pub const PADDLE_SPEED_NORMAL: f32 = 8.0;
-pub const PADDLE_SPEED_HIGH: f32 = 13.0;
-pub const BALL_SPEED_NORMAL: f32 = 5.0;
-pub const BALL_SPEED_HARD: f32 = 9.0;
-pub const PADDLE_SPIN: f32 = 4.0;
-pub const BALL_ACC: f32 = 0.05;
+// smallest playfield, on which the paddles and the ball still have room to move
+pub const MIN_FIELD_WIDTH: f32 = 320.0;
+pub const MIN_FIELD_HEIGHT: f32 = 240.0;
+// all speeds are given in units per second
+pub const PADDLE_SPEED_NORMAL: f32 = 480.0;
+pub const PADDLE_SPEED_HIGH: f32 = 780.0;
+pub const BALL_SPEED_NORMAL: f32 = 300.0;
+pub const BALL_SPEED_HARD: f32 = 540.0;
+pub const PADDLE_SPIN: f32 = 240.0;
+pub const BALL_ACC: f32 = 3.0;
+// size of the paddle and the ball sprites in the resources, for a simulation without the sprites
+pub const PADDLE_SIZE: Vec2<f32> = Vec2::new(24.0, 104.0);
+pub const BALL_SIZE: Vec2<f32> = Vec2::new(22.0, 22.0);
 pub const SCORE_TEXT_OFFSET: Vec2<f32> = Vec2::new(32.0, 16.0);
 pub const SCORE_TEXT_SIZE: f32 = 21.0;
-pub const CENTER_LINE_SIZE: f32 = 18.0;
 pub const MAIN_MENU_HEADER_SIZE: f32 = 20.0;
 pub const MAIN_MENU_USAGE_SIZE: f32 = 15.0;
-pub const FREEZE_UPDATE_RATE: f64 = 0.000001;
 pub const DEFAULT_UPDATE_RATE: f64 = 60.0;
+// duration of one tick of the simulation in seconds, independent of the frame rate
+pub const SIMULATION_TIMESTEP: f32 = (1.0 / DEFAULT_UPDATE_RATE) as f32;
 pub const SCORE_LIMIT: u8 = 15;
-pub const PONG_GAME_FONT: &str = "./resources/pong.ttf";
-pub const MAIN_MENU_FONT: &str = "./resources/comic.ttf";
-pub const DASHED_MIDDLE_LINE: &str = "|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|
-|";
-
-pub const MAIN_MENU_HEADER: &str = ">---- The Pong-Game ----<";
-pub const MAIN_MENU_USAGE: &str = "ESC             =>  Quit game
-P                => Pause/Resume
-Backspace => Main menu
-
-Player 1 (Left hand side):
-- Use 'W' and 'S' to move the paddle
-
-Player 2 (Right hand side):
-- Use 'UP' and 'DOWN' to move the paddle
-
-Start playing, choose game play mode:
---------------------------------------
-N    =>  Normal
-H    =>  Hard";
-
-pub const WIN_MESSAGE: &str = "   > You win the game <
-
-Backspace => Main menu";
+// language which is always available and used for every missing string
+pub const FALLBACK_LANGUAGE: &str = "en";
+pub const PONG_GAME_FONT: &str = "pong.ttf";
+pub const MAIN_MENU_FONT: &str = "comic.ttf";
+// part of the range of a gamepad stick around its centre, which will be ignored
+pub const GAMEPAD_DEADZONE: f32 = 0.2;
+// number of replay files, which will be kept in the replay directory
+pub const REPLAY_KEEP: u32 = 20;
+// seconds without any input during a match, until the game goes back to the demo in the main menu
+pub const DEMO_IDLE_TIMEOUT: u32 = 60;
+// distance the paddle follows a captured mouse per pixel of mouse movement
+pub const MOUSE_SENSITIVITY: f32 = 1.0;
+// default theme of the playfield, all colors are given as RGBA
+pub const BACKGROUND_COLOR: [u8; 4] = [100, 149, 237, 255];
+pub const LINE_COLOR: [u8; 4] = [255, 255, 255, 255];
+pub const GOAL_AREA_COLOR: [u8; 4] = [255, 255, 255, 40];
+pub const LINE_WIDTH: f32 = 4.0;
+pub const NET_DASH_LENGTH: f32 = 12.0;
+pub const NET_GAP_LENGTH: f32 = 8.0;
+pub const GOAL_AREA_WIDTH: f32 = 16.0;